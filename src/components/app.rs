@@ -1,24 +1,33 @@
 use yew::prelude::*;
 
+use crate::models::user::User;
 use crate::router;
+use crate::routes::{AppRoute, RouteError};
+use crate::store;
 
+use crate::components::archive::Model as ArchiveModel;
+use crate::components::avatar::Model as AvatarModel;
 use crate::components::board::Model as BoardModel;
+use crate::components::team_list::Model as TeamListModel;
 use crate::components::user_list::Model as UserListModel;
-
-pub enum Child {
-  Board,
-  UserList,
-  PathNotFound(String),
-}
+use crate::components::user_profile::Model as UserProfileModel;
+use crate::components::workflow_editor::Model as WorkflowEditorModel;
 
 pub struct Model {
-  child: Child,
+  /// What the current route asks for, or why it could not be understood.
+  child: Result<AppRoute, RouteError>,
   router: Box<Bridge<router::Router<()>>>,
+  /// Held for the lifetime of the app so the store outlives the screens that come and go.
+  store: Box<Bridge<store::Store>>,
+  /// Only the users and who is signed in are of interest here, for the navbar.
+  state: store::State,
 }
 
 pub enum Msg {
-  NavigateTo(Child),
+  NavigateTo(AppRoute),
   HandleRoute(router::Route<()>),
+  StoreChanged(store::State),
+  SwitchUser(yew::html::ChangeData),
 }
 
 impl Component for Model {
@@ -36,48 +45,43 @@ impl Component for Model {
     // router worker logic after the mounting has finished.
     router.send(router::Request::GetCurrentRoute);
 
+    let mut store = store::Store::bridge(link.send_back(|state: store::State| Msg::StoreChanged(state)));
+    store.send(store::Request::GetState);
+
     Model {
-      child: Child::Board, // This should be quickly overwritten by the actual route.
+      child: Ok(AppRoute::Board), // This should be quickly overwritten by the actual route.
       router,
+      store,
+      state: store::State::default(),
     }
   }
 
   fn update(&mut self, msg: Self::Message) -> ShouldRender {
     match msg {
-      Msg::NavigateTo(child) => {
-        let path_segments = match child {
-          Child::Board => vec!["board".into()],
-          Child::UserList => vec!["users".into()],
-          Child::PathNotFound(_) => vec!["path_not_found".into()],
-        };
-
-        let route = router::Route {
-          path_segments,
-          query: None,
-          fragment: None,
-          state: (),
-        };
-
-        self.router.send(router::Request::ChangeRoute(route));
+      Msg::NavigateTo(route) => {
+        self.router.send(router::Request::ChangeRoute(route.to_route()));
         false
       }
       Msg::HandleRoute(route) => {
         info!("Routing: {}", route.to_route_string());
-        // Instead of each component selecting which parts of the path are important to it,
-        // it is also possible to match on the `route.to_route_string().as_str()` once
-        // and create enum variants representing the different children and pass them as props.
-        self.child = if let Some(first_segment) = route.path_segments.get(0) {
-          match first_segment.as_str() {
-            "board" => Child::Board,
-            "users" => Child::UserList,
-            other => Child::PathNotFound(other.into()),
-          }
-        } else {
-          Child::PathNotFound("path_not_found".into())
-        };
-
+        self.child = AppRoute::from_route(&route);
         true
       }
+      Msg::StoreChanged(state) => {
+        let changed = state.users != self.state.users
+          || state.current_user != self.state.current_user
+          || state.storage_error != self.state.storage_error;
+        self.state = state;
+        changed
+      }
+      Msg::SwitchUser(val) => {
+        if let yew::html::ChangeData::Select(v) = &val {
+          if let Ok(id) = u32::from_str_radix(&v.raw_value(), 10) {
+            self.store.send(store::Request::Dispatch(store::Action::SwitchUser(id)));
+          }
+        }
+        false
+      }
     }
   }
 }
@@ -86,50 +90,134 @@ impl Renderable<Model> for Model {
   fn view(&self) -> Html<Self> {
     html! {
       <div>
-        {view_nav()}
+        {view_nav(&self.state)}
+        {view_storage_error(&self.state)}
         <section class="section",>
-          {self.child.view()}
+          {view_child(&self.child)}
         </section>
       </div>
     }
   }
 }
 
-impl Renderable<Model> for Child {
-  fn view(&self) -> Html<Model> {
-    match *self {
-      Child::Board => html! {
-          <>
-              <BoardModel: />
-          </>
-      },
-      Child::UserList => html! {
-          <>
-              <UserListModel: />
-          </>
-      },
-      Child::PathNotFound(ref path) => html! {
-          <>
-              {format!("Not found: '{}'", path)}
-          </>
-      },
-    }
+fn view_child(child: &Result<AppRoute, RouteError>) -> Html<Model> {
+  match *child {
+    Ok(AppRoute::Board) => html! {
+        <>
+            <BoardModel: selected=None,/>
+        </>
+    },
+    Ok(AppRoute::Task(id)) => html! {
+        <>
+            <BoardModel: selected=Some(id),/>
+        </>
+    },
+    Ok(AppRoute::Archive) => html! {
+        <>
+            <ArchiveModel: />
+        </>
+    },
+    Ok(AppRoute::Users) => html! {
+        <>
+            <UserListModel: />
+        </>
+    },
+    Ok(AppRoute::User(id)) => html! {
+        <>
+            <UserProfileModel: user_id=id,/>
+        </>
+    },
+    Ok(AppRoute::Teams) => html! {
+        <>
+            <TeamListModel: />
+        </>
+    },
+    Ok(AppRoute::Workflow) => html! {
+        <>
+            <WorkflowEditorModel: />
+        </>
+    },
+    Err(ref error) => html! {
+        <>
+            {error.to_string()}
+        </>
+    },
   }
 }
 
-fn view_nav() -> Html<Model> {
+fn view_storage_error(state: &store::State) -> Html<Model> {
+  match state.storage_error {
+    Some(ref error) => html! {
+      <div class="notification is-danger",>{ error }</div>
+    },
+    None => html! {
+      <></>
+    },
+  }
+}
+
+fn view_nav(state: &store::State) -> Html<Model> {
   html! {
     <nav class="navbar", role="navigation", aria-label="main navigation",>
       <div class="navbar-menu",>
         <div class="navbar-start",>
-          <a class="navbar-item", onclick=|_| Msg::NavigateTo(Child::Board),>
+          <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::Board),>
             { "board" }
           </a>
-          <a class="navbar-item", onclick=|_| Msg::NavigateTo(Child::UserList),>
+          <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::Archive),>
+            { "archive" }
+          </a>
+          <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::Users),>
             { "users" }
           </a>
+          <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::Teams),>
+            { "teams" }
+          </a>
+          <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::Workflow),>
+            { "workflow" }
+          </a>
+        </div>
+        <div class="navbar-end",>
+          { view_session(state) }
         </div>
       </div>
     </nav>
   }
 }
+
+/// Who the app acts for, with a switcher to pick somebody else.
+fn view_session(state: &store::State) -> Html<Model> {
+  let current = state.signed_in_user().cloned();
+  let selected = state.current_user.map(|id| id.to_string()).unwrap_or_default();
+  let my_tasks = match state.current_user {
+    Some(id) => html! {
+      <a class="navbar-item", onclick=|_| Msg::NavigateTo(AppRoute::User(id)),>
+        { "my tasks" }
+      </a>
+    },
+    None => html! {
+      <></>
+    },
+  };
+
+  html! {
+    <>
+      { my_tasks }
+      <div class="navbar-item",>
+        <AvatarModel: user=current,/>
+        <div class="select is-small",>
+          <select value=selected, onchange=|e| Msg::SwitchUser(e),>
+            <option value="", disabled=true,>{ "Sign in as…" }</option>
+            { for state.users.iter().map(view_session_option) }
+          </select>
+        </div>
+      </div>
+    </>
+  }
+}
+
+fn view_session_option(user: &User) -> Html<Model> {
+  html! {
+    <option value=user.id.to_string(),>{ format!("{} ({})", user.full_name(), user.role) }</option>
+  }
+}
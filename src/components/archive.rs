@@ -0,0 +1,105 @@
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+use crate::components::avatar::Model as AvatarModel;
+use crate::models::role::Permission;
+use crate::models::task::{Task, TaskId};
+use crate::models::user::User;
+use crate::store::{Action, Request, State, Store};
+
+pub enum Msg {
+    StateChanged(State),
+    Restore(TaskId),
+}
+
+pub struct Model {
+    store: Box<Bridge<Store>>,
+    state: State,
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|state: State| Msg::StateChanged(state));
+        let mut store = Store::bridge(callback);
+        store.send(Request::GetState);
+
+        Model {
+            store,
+            state: State::default(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::StateChanged(state) => {
+                self.state = state;
+                true
+            }
+
+            Msg::Restore(id) => {
+                if !self.state.current_role().allows(Permission::EditTasks) {
+                    return false;
+                }
+                self.store.send(Request::Dispatch(Action::SetArchived(id, false)));
+                false
+            }
+        }
+    }
+}
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        let archived: Vec<&Task> = self.state.tasks.iter().filter(|task| task.archived).collect();
+
+        html! {
+            <div class="container",>
+                <h2>{ "Archive" }</h2>
+                { view_list(&archived, &self.state.users, self.state.current_role().allows(Permission::EditTasks)) }
+            </div>
+        }
+    }
+}
+
+fn view_list(tasks: &[&Task], users: &[User], can_restore: bool) -> Html<Model> {
+    if tasks.is_empty() {
+        return html! {
+            <p>{ "Nothing has been archived yet." }</p>
+        };
+    }
+
+    html! {
+        <table class="table is-fullwidth",>
+            <tbody>
+                { for tasks.iter().map(|task| view_item(task, users, can_restore)) }
+            </tbody>
+        </table>
+    }
+}
+
+fn view_item(task: &Task, users: &[User], can_restore: bool) -> Html<Model> {
+    let id = task.id;
+    let assignee = task
+        .assignee
+        .and_then(|id| users.iter().find(|user| user.id == id))
+        .cloned();
+    let name = match assignee {
+        Some(ref user) => user.full_name(),
+        None => "Unassigned".to_string(),
+    };
+
+    html! {
+        <tr>
+            <td>{ &task.name }</td>
+            <td>
+                <AvatarModel: user=assignee,/>
+                { name }
+            </td>
+            <td>{ format!("{}h", &task.estimate) }</td>
+            <td>
+                <button class="button is-small", disabled=!can_restore, onclick=|_| Msg::Restore(id),>{ "Restore to board" }</button>
+            </td>
+        </tr>
+    }
+}
@@ -0,0 +1,61 @@
+//! The one place that knows how to draw a user's avatar.
+
+use yew::prelude::{Component, ComponentLink, Html, Renderable, ShouldRender};
+
+use crate::models::user::{Avatar, User};
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Props {
+    pub user: Option<User>,
+}
+
+pub struct Model {
+    user: Option<User>,
+}
+
+impl Component for Model {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        Model { user: props.user }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.user = props.user;
+        true
+    }
+}
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        let user = match self.user {
+            Some(ref user) => user,
+            None => {
+                return html! {
+                    <span class="avatar is-unassigned",>{ "?" }</span>
+                };
+            }
+        };
+
+        match user.avatar {
+            Avatar::Emoji(ref emoji) => html! {
+                <span class="avatar is-emoji", title=user.full_name(),>{ emoji }</span>
+            },
+            Avatar::Image(ref data_url) => html! {
+                <img class="avatar is-image", src=data_url, alt=user.full_name(), title=user.full_name(), />
+            },
+            Avatar::Empty | Avatar::Initials => html! {
+                <span class="avatar is-initials",
+                    style=format!("background-color: {}", user.color()),
+                    title=user.full_name(),>
+                    { user.initials() }
+                </span>
+            },
+        }
+    }
+}
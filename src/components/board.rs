@@ -1,144 +1,675 @@
 use std::convert::From;
-use yew::prelude::{Component, ComponentLink, Html, Renderable, ShouldRender};
+use stdweb::web::event::{IDragEvent, IEvent};
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
+use yew::services::DialogService;
 use yew::virtual_dom::VNode;
 
+use crate::components::avatar::Model as AvatarModel;
+use crate::models::role::Permission;
 use crate::models::status::Status;
-use crate::models::task::Task;
+use crate::models::task::{Task, TaskId};
+use crate::models::team::{Team, TeamId};
+use crate::models::user::User;
+use crate::models::workflow::{Column, Rule, Workflow};
+use crate::router;
+use crate::routes::AppRoute;
+use crate::store::{Action, Request, State, Store};
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Props {
+    /// The task whose detail panel is open, taken from the route.
+    pub selected: Option<TaskId>,
+}
 
 pub enum Msg {
-    IncreaseStatus(usize),
-    DecreaseStatus(usize),
+    StateChanged(State),
+    /// The router broadcasts every route change, the app passes the relevant ones down as props.
+    RouteChanged,
+    OpenDetail(TaskId),
+    CloseDetail,
+    IncreaseStatus(TaskId),
+    DecreaseStatus(TaskId),
     UpdateNewTaskName(String),
     UpdateNewTaskAssignee(yew::html::ChangeData),
+    UpdateNewTaskTeam(yew::html::ChangeData),
     UpdateNewTaskMandays(String),
     NewTask,
+    EditTask(TaskId),
+    UpdateEditName(String),
+    UpdateEditAssignee(yew::html::ChangeData),
+    UpdateEditTeam(yew::html::ChangeData),
+    UpdateEditMandays(String),
+    UpdateTask,
+    CancelEdit,
+    DeleteTask(TaskId),
+    UndoDelete,
+    ArchiveTask(TaskId),
+    DragStart(TaskId),
+    DragOver(Status),
+    DragEnd,
+    /// Moves a task into `to`, placing it before the `position`-th task of that column.
+    MoveTask {
+        id: TaskId,
+        to: Status,
+        position: usize,
+    },
+    UpdateReason(String),
+    ConfirmMove,
+    CancelMove,
+    /// Moves a task into a column that reached its WIP limit anyway.
+    OverrideLimit,
+    DismissError,
+    FilterTeam(yew::html::ChangeData),
+}
+
+/// The messages sent by a set of task inputs, so the same inputs serve both
+/// the new-task header and the edit form on a card.
+#[derive(Clone, Copy)]
+struct TaskForm {
+    name: fn(String) -> Msg,
+    assignee: fn(yew::html::ChangeData) -> Msg,
+    team: fn(yew::html::ChangeData) -> Msg,
+    estimate: fn(String) -> Msg,
+}
+
+const NEW_TASK_FORM: TaskForm = TaskForm {
+    name: Msg::UpdateNewTaskName,
+    assignee: Msg::UpdateNewTaskAssignee,
+    team: Msg::UpdateNewTaskTeam,
+    estimate: Msg::UpdateNewTaskMandays,
+};
+
+const EDIT_TASK_FORM: TaskForm = TaskForm {
+    name: Msg::UpdateEditName,
+    assignee: Msg::UpdateEditAssignee,
+    team: Msg::UpdateEditTeam,
+    estimate: Msg::UpdateEditMandays,
+};
+
+/// A move that waits for the user, either to explain it (see `Rule::NeedsReason`)
+/// or to let it exceed a column's WIP limit.
+struct PendingMove {
+    id: TaskId,
+    to: Status,
+    position: usize,
+    reason: String,
 }
 
 pub struct Model {
-    tasks: Vec<Task>,
-    new_task: Task,
+    store: Box<Bridge<Store>>,
+    router: Box<Bridge<router::Router<()>>>,
+    dialog: DialogService,
+    state: State,
+    /// A copy of the task being edited, written back on `Msg::UpdateTask`.
+    editing: Option<Task>,
+    /// The last deleted task and where it was, kept so the deletion can be undone.
+    deleted: Option<(usize, Task)>,
+    dragging: Option<TaskId>,
+    drop_target: Option<Status>,
+    pending_move: Option<PendingMove>,
+    over_limit_move: Option<PendingMove>,
+    error: Option<String>,
+    /// Only tasks of this team are shown, see `State::is_team_task`.
+    team_filter: Option<TeamId>,
+    selected: Option<TaskId>,
+    /// Whether the detail panel was opened from the board, rather than from a pasted link
+    /// that has no board to go back to.
+    opened_here: bool,
+    guard: router::ConfirmGuard,
+    /// Set while the board waits for a route it asked for itself to come back as props.
+    /// Those keep the board mounted, so its guard is lifted meanwhile.
+    navigating: bool,
 }
 
 impl Model {
-    fn find_task_by(&mut self, idx: usize) -> Option<&mut Task> {
-        self.tasks.get_mut(idx)
+    fn dispatch(&mut self, action: Action) {
+        self.store.send(Request::Dispatch(action));
     }
 
-    fn clear_form(&mut self) {
-        self.new_task = Task::create_empty();
+    fn status_of(&self, id: TaskId) -> Option<Status> {
+        self.state.task(id).map(|task| task.status.clone())
     }
 
-    fn add_new_task(&mut self, name: String, assignee: String, estimate: u32) {
-        self.tasks.push(Task {
-            name,
-            assignee,
-            estimate,
-            status: Status::ToDo,
-        });
+    fn label_of(&self, status: &Status) -> String {
+        match self.state.workflow.column(status) {
+            Some(column) => column.label.clone(),
+            None => status.to_string(),
+        }
     }
-}
 
-impl Component for Model {
-    type Message = Msg;
-    type Properties = ();
+    fn count_in(&self, status: &Status) -> usize {
+        self.state.column_tasks(status).count()
+    }
 
-    fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
-        Model {
-            tasks: vec![],
-            new_task: Task::create_empty(),
+    /// Changes to a route that keeps the board mounted, without asking its own guard.
+    fn navigate(&mut self, request: router::Request<()>) {
+        self.navigating = true;
+        self.guard.update(&mut self.router, None);
+        self.router.send(request);
+    }
+
+    /// Opens the detail panel by changing the route, which comes back as props.
+    fn open_detail(&mut self, id: TaskId) {
+        if self.selected != Some(id) {
+            self.navigate(router::Request::ChangeRoute(AppRoute::Task(id).to_route()));
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+    /// Closes the detail panel by going back to the board it was opened from, or by
+    /// swapping a pasted link for the board so the history has no dead end.
+    fn close_detail(&mut self) {
+        let request = match self.opened_here {
+            true => router::Request::Back,
+            false => router::Request::ReplaceRoute(AppRoute::Board.to_route()),
+        };
+        self.navigate(request);
+    }
+
+    /// What leaving the board would throw away. The new-task header is left out,
+    /// the store keeps its draft.
+    fn unsaved_changes(&self) -> Option<String> {
+        if let Some(ref edited) = self.editing {
+            let changed = self.state.task(edited.id).map_or(true, |task| {
+                task.name != edited.name
+                    || task.assignee != edited.assignee
+                    || task.team != edited.team
+                    || task.estimate != edited.estimate
+            });
+            if changed {
+                return Some(format!("Discard your changes to \"{}\"?", edited.name));
+            }
+        }
+        match self.pending_move {
+            Some(ref pending) if !pending.reason.trim().is_empty() => {
+                Some("Discard the reason you started typing?".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Guards `unsaved_changes`, unless the board is on its way to one of its own routes.
+    fn update_guard(&mut self) {
+        if !self.navigating {
+            let unsaved = self.unsaved_changes();
+            self.guard.update(&mut self.router, unsaved);
+        }
+    }
+
+    fn can(&self, permission: Permission) -> bool {
+        self.state.current_role().allows(permission)
+    }
+
+    fn is_shown(&self, task: &Task) -> bool {
+        match self.team_filter {
+            Some(team) => self.state.is_team_task(task, team),
+            None => true,
+        }
+    }
+
+    /// Moves a task one column along the workflow, `step` picks the neighbouring column.
+    fn step_task(&mut self, id: TaskId, step: fn(&Workflow, &Status) -> Option<Status>) {
+        let next = self
+            .status_of(id)
+            .and_then(|status| step(&self.state.workflow, &status));
+        if let Some(to) = next {
+            self.request_move(id, to, usize::max_value(), false);
+        }
+    }
+
+    /// Checks a move against the workflow's transition rules and WIP limits before carrying it out.
+    fn request_move(&mut self, id: TaskId, to: Status, position: usize, override_limit: bool) {
+        let from = match self.status_of(id) {
+            Some(from) => from,
+            None => return,
+        };
+        let rule = self.state.workflow.rule(&from, &to);
+
+        let is_full = from != to
+            && self.state.workflow.block_full_columns
+            && self
+                .state
+                .workflow
+                .column(&to)
+                .map_or(false, |column| column.is_full(self.count_in(&to)));
+        if rule.is_some() && is_full && !override_limit {
+            self.over_limit_move = Some(PendingMove {
+                id,
+                to,
+                position,
+                reason: "".to_string(),
+            });
+            return;
+        }
+
+        match rule {
+            Some(Rule::Allowed) => self.dispatch(Action::MoveTask {
+                id,
+                to,
+                position,
+                reason: None,
+            }),
+            Some(Rule::NeedsReason) => {
+                self.pending_move = Some(PendingMove {
+                    id,
+                    to,
+                    position,
+                    reason: "".to_string(),
+                });
+            }
+            None => {
+                self.error = Some(format!(
+                    "Tasks cannot be moved from \"{}\" to \"{}\".",
+                    self.label_of(&from),
+                    self.label_of(&to)
+                ));
+            }
+        }
+    }
+
+    fn apply(&mut self, msg: Msg) -> ShouldRender {
+        if let Some(permission) = required_permission(&msg) {
+            if !self.can(permission) {
+                self.error = Some(format!(
+                    "As {} you are not allowed to do that.",
+                    self.state.current_role()
+                ));
+                return true;
+            }
+        }
+
         match msg {
+            Msg::StateChanged(state) => {
+                self.state = state;
+            }
+
+            Msg::RouteChanged => return false,
+
+            Msg::OpenDetail(id) => {
+                self.open_detail(id);
+                return false;
+            }
+
+            Msg::CloseDetail => {
+                self.close_detail();
+                return false;
+            }
+
             Msg::UpdateNewTaskName(val) => {
-                self.new_task.name = val;
+                let mut new_task = self.state.new_task.clone();
+                new_task.name = val;
+                self.dispatch(Action::UpdateNewTask(new_task));
             }
 
             Msg::UpdateNewTaskAssignee(val) => {
                 if let yew::html::ChangeData::Select(v) = &val {
-                    self.new_task.assignee = v.raw_value();
+                    let mut new_task = self.state.new_task.clone();
+                    new_task.assignee = parse_id(&v.raw_value());
+                    self.dispatch(Action::UpdateNewTask(new_task));
+                }
+            }
+
+            Msg::UpdateNewTaskTeam(val) => {
+                if let yew::html::ChangeData::Select(v) = &val {
+                    let mut new_task = self.state.new_task.clone();
+                    new_task.team = parse_id(&v.raw_value());
+                    self.dispatch(Action::UpdateNewTask(new_task));
                 }
             }
 
             Msg::UpdateNewTaskMandays(val) => {
                 if let Ok(v) = u32::from_str_radix(&val, 10) {
-                    self.new_task.estimate = v;
+                    let mut new_task = self.state.new_task.clone();
+                    new_task.estimate = v;
+                    self.dispatch(Action::UpdateNewTask(new_task));
                 }
             }
 
             Msg::NewTask => {
-                self.add_new_task(
-                    self.new_task.name.clone(),
-                    self.new_task.assignee.clone(),
-                    self.new_task.estimate,
-                );
-                self.clear_form();
+                let new_task = self.state.new_task.clone();
+                self.dispatch(Action::AddTask(new_task));
+                self.dispatch(Action::UpdateNewTask(Task::create_empty()));
+            }
+
+            Msg::EditTask(id) => {
+                self.editing = self.state.task(id).cloned();
             }
 
-            Msg::IncreaseStatus(idx) => match self.find_task_by(idx) {
-                None => (),
-                Some(task) => {
-                    task.status = task.status.right();
+            Msg::UpdateEditName(val) => {
+                if let Some(task) = self.editing.as_mut() {
+                    task.name = val;
                 }
-            },
+            }
 
-            Msg::DecreaseStatus(idx) => match self.find_task_by(idx) {
-                None => (),
-                Some(task) => {
-                    task.status = task.status.left();
+            Msg::UpdateEditAssignee(val) => {
+                if let (Some(task), yew::html::ChangeData::Select(v)) = (self.editing.as_mut(), &val) {
+                    task.assignee = parse_id(&v.raw_value());
                 }
-            },
+            }
+
+            Msg::UpdateEditTeam(val) => {
+                if let (Some(task), yew::html::ChangeData::Select(v)) = (self.editing.as_mut(), &val) {
+                    task.team = parse_id(&v.raw_value());
+                }
+            }
+
+            Msg::UpdateEditMandays(val) => {
+                if let (Some(task), Ok(v)) = (self.editing.as_mut(), u32::from_str_radix(&val, 10)) {
+                    task.estimate = v;
+                }
+            }
+
+            Msg::UpdateTask => {
+                if let Some(edited) = self.editing.take() {
+                    self.dispatch(Action::UpdateTask(edited));
+                }
+            }
+
+            Msg::CancelEdit => {
+                self.editing = None;
+            }
+
+            Msg::DeleteTask(id) => {
+                let idx = match self.state.tasks.iter().position(|task| task.id == id) {
+                    Some(idx) => idx,
+                    None => return false,
+                };
+                let question = format!("Delete \"{}\"?", self.state.tasks[idx].name);
+                if !self.dialog.confirm(&question) {
+                    return false;
+                }
+                self.deleted = Some((idx, self.state.tasks[idx].clone()));
+                self.dispatch(Action::DeleteTask(id));
+            }
+
+            Msg::UndoDelete => {
+                if let Some((idx, task)) = self.deleted.take() {
+                    self.dispatch(Action::RestoreTask(idx, task));
+                }
+            }
+
+            Msg::ArchiveTask(id) => self.dispatch(Action::SetArchived(id, true)),
+
+            Msg::IncreaseStatus(id) => self.step_task(id, Workflow::right),
+
+            Msg::DecreaseStatus(id) => self.step_task(id, Workflow::left),
+
+            Msg::DragStart(id) => {
+                self.dragging = Some(id);
+            }
+
+            Msg::DragOver(status) => {
+                // `dragover` fires continuously, only re-render when the target column changes.
+                if self.drop_target.as_ref() == Some(&status) {
+                    return false;
+                }
+                self.drop_target = Some(status);
+            }
+
+            Msg::DragEnd => {
+                self.dragging = None;
+                self.drop_target = None;
+            }
+
+            Msg::MoveTask { id, to, position } => {
+                self.dragging = None;
+                self.drop_target = None;
+                self.request_move(id, to, position, false);
+            }
+
+            Msg::UpdateReason(val) => {
+                if let Some(pending) = self.pending_move.as_mut() {
+                    pending.reason = val;
+                }
+            }
+
+            Msg::ConfirmMove => {
+                let pending = match self.pending_move.take() {
+                    Some(pending) => pending,
+                    None => return false,
+                };
+                if pending.reason.trim().is_empty() {
+                    self.pending_move = Some(pending);
+                    return false;
+                }
+                self.dispatch(Action::MoveTask {
+                    id: pending.id,
+                    to: pending.to,
+                    position: pending.position,
+                    reason: Some(pending.reason.trim().to_string()),
+                });
+            }
+
+            Msg::CancelMove => {
+                self.pending_move = None;
+                self.over_limit_move = None;
+            }
+
+            Msg::OverrideLimit => {
+                if let Some(pending) = self.over_limit_move.take() {
+                    self.request_move(pending.id, pending.to, pending.position, true);
+                }
+            }
+
+            Msg::DismissError => {
+                self.error = None;
+            }
+
+            Msg::FilterTeam(val) => {
+                if let yew::html::ChangeData::Select(v) = &val {
+                    self.team_filter = parse_id(&v.raw_value());
+                }
+            }
         }
         true
     }
 }
 
+impl Component for Model {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|state: State| Msg::StateChanged(state));
+        let mut store = Store::bridge(callback);
+        store.send(Request::GetState);
+
+        let router = router::Router::bridge(link.send_back(|_: router::Route<()>| Msg::RouteChanged));
+
+        Model {
+            store,
+            router,
+            dialog: DialogService::new(),
+            state: State::default(),
+            editing: None,
+            deleted: None,
+            dragging: None,
+            drop_target: None,
+            pending_move: None,
+            over_limit_move: None,
+            error: None,
+            team_filter: None,
+            selected: props.selected,
+            opened_here: false,
+            guard: router::ConfirmGuard::default(),
+            navigating: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let render = self.apply(msg);
+        self.update_guard();
+        render
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props.selected != self.selected {
+            // Only a panel the board opened itself has the board to go back to.
+            self.opened_here = self.navigating && props.selected.is_some();
+            self.navigating = false;
+        }
+        self.selected = props.selected;
+        self.update_guard();
+        true
+    }
+}
+
+/// The permission a message needs, `None` for those that leave the tasks alone.
+fn required_permission(msg: &Msg) -> Option<Permission> {
+    match msg {
+        Msg::IncreaseStatus(_)
+        | Msg::DecreaseStatus(_)
+        | Msg::UpdateNewTaskName(_)
+        | Msg::UpdateNewTaskAssignee(_)
+        | Msg::UpdateNewTaskTeam(_)
+        | Msg::UpdateNewTaskMandays(_)
+        | Msg::NewTask
+        | Msg::EditTask(_)
+        | Msg::UpdateEditName(_)
+        | Msg::UpdateEditAssignee(_)
+        | Msg::UpdateEditTeam(_)
+        | Msg::UpdateEditMandays(_)
+        | Msg::UpdateTask
+        | Msg::ArchiveTask(_)
+        | Msg::DragStart(_)
+        | Msg::MoveTask { .. }
+        | Msg::UpdateReason(_)
+        | Msg::ConfirmMove
+        | Msg::OverrideLimit => Some(Permission::EditTasks),
+        Msg::DeleteTask(_) | Msg::UndoDelete => Some(Permission::DeleteTasks),
+        Msg::StateChanged(_)
+        | Msg::RouteChanged
+        | Msg::OpenDetail(_)
+        | Msg::CloseDetail
+        | Msg::DragOver(_)
+        | Msg::DragEnd
+        | Msg::CancelEdit
+        | Msg::CancelMove
+        | Msg::DismissError
+        | Msg::FilterTeam(_) => None,
+    }
+}
+
 impl Renderable<Model> for Model {
     fn view(&self) -> Html<Self> {
-        let Model { tasks, .. } = &self;
-
         html! {
             <div class="container",>
                 { view_header(&self) }
+                { view_error(&self) }
+                { view_undo(&self) }
+                { view_limit_warning(&self) }
+                { view_team_filter(&self) }
+                { view_workloads(&self) }
                 <div class="columns",>
-                    { view_column(Status::ToDo, tasks) }
-                    { view_column(Status::InProgress, tasks) }
-                    { view_column(Status::Review, tasks) }
-                    { view_column(Status::Testing, tasks) }
-                    { view_column(Status::Ready, tasks) }
-                    { view_column(Status::Done, tasks) }
+                    { for self.state.workflow.columns.iter().map(|column| view_column(column, &self)) }
                 </div>
+                { view_reason_modal(&self) }
+                { view_detail(&self) }
             </div>
         }
     }
 }
 
-fn view_column(status: Status, tasks: &Vec<Task>) -> Html<Model> {
+fn view_column(column: &Column, model: &Model) -> Html<Model> {
+    let status = &column.key;
+    // Positions count every task of the column, including those the team filter hides.
+    let tasks: Vec<(usize, &Task)> = model
+        .state
+        .column_tasks(status)
+        .enumerate()
+        .filter(|(_, task)| model.is_shown(task))
+        .collect();
+    let dragged_from = model.dragging.and_then(|id| model.status_of(id));
+    let class = match dragged_from {
+        Some(ref from) if model.state.workflow.rule(from, status).is_none() => {
+            format!("column status-{} is-drop-forbidden", status)
+        }
+        Some(_) if model.drop_target.as_ref() == Some(status) => {
+            format!("column status-{} is-drop-target", status)
+        }
+        _ => format!("column status-{}", status),
+    };
+
+    let dragging = model.dragging;
+    let count = model.count_in(status);
+    let over_status = status.clone();
+    let drop_status = status.clone();
+
     html! {
-        <div class=format!("column is-2 status-{}", status.to_string()),>
+        <div class=class,
+            style=format!("background-color: {}", column.color),
+            ondragover=|e| {
+                // Dropping is only allowed where `dragover` is cancelled.
+                e.prevent_default();
+                Msg::DragOver(over_status.clone())
+            },
+            ondrop=|e| {
+                e.prevent_default();
+                match dragging {
+                    Some(id) => Msg::MoveTask { id, to: drop_status.clone(), position: count },
+                    None => Msg::DragEnd,
+                }
+            },>
             <div class="tags has-addons",>
-                <span class="tag",>{ status.name() }</span>
-                <span class="tag is-dark",>{ tasks.iter().filter(|e| e.status == status).count() }</span>
+                <span class="tag",>{ &column.label }</span>
+                { view_count(column, count) }
             </div>
-            { for tasks.iter().enumerate().filter(|(_, e)| e.status == status).map(view_task) }
+            { for tasks.iter().map(|(position, task)| view_task(task, *position, model)) }
         </div>
     }
 }
 
-fn view_task((idx, task): (usize, &Task)) -> Html<Model> {
-    let button_left: VNode<Model> = match task.can_left() {
+fn view_count(column: &Column, count: usize) -> Html<Model> {
+    match column.limit {
+        Some(limit) => {
+            let class = if column.is_over_limit(count) {
+                "tag is-danger"
+            } else {
+                "tag is-dark"
+            };
+            html! {
+                <span class=class,>{ format!("{}/{}", count, limit) }</span>
+            }
+        }
+        None => html! {
+            <span class="tag is-dark",>{ count }</span>
+        },
+    }
+}
+
+fn view_task(task: &Task, position: usize, model: &Model) -> Html<Model> {
+    if let Some(ref edited) = model.editing {
+        if edited.id == task.id {
+            return view_edit_form(edited, model);
+        }
+    }
+
+    let id = task.id;
+    let dragging = model.dragging;
+    let status = task.status.clone();
+    let class = if dragging == Some(id) {
+        "card is-dragging"
+    } else {
+        "card"
+    };
+    let can_edit = model.can(Permission::EditTasks);
+
+    let can_move = |to: Option<Status>| match to {
+        Some(to) => can_edit && model.state.workflow.rule(&task.status, &to).is_some(),
+        None => false,
+    };
+
+    let button_left: VNode<Model> = match can_move(model.state.workflow.left(&task.status)) {
         true => html! {
-            <button class="button is-small is-white", onclick=|_| Msg::DecreaseStatus(idx),>{ "◀︎" }</button>
+            <button class="button is-small is-white", onclick=|_| Msg::DecreaseStatus(id),>{ "◀︎" }</button>
         },
         false => html! {
             <></>
         },
     };
 
-    let button_right: VNode<Model> = match task.can_right() {
+    let button_right: VNode<Model> = match can_move(model.state.workflow.right(&task.status)) {
         true => html! {
-            <button class="button is-small is-white", onclick=|_| Msg::IncreaseStatus(idx),>{ "▶︎︎" }</button>
+            <button class="button is-small is-white", onclick=|_| Msg::IncreaseStatus(id),>{ "▶︎︎" }</button>
         },
         false => html! {
             <></>
@@ -146,13 +677,29 @@ fn view_task((idx, task): (usize, &Task)) -> Html<Model> {
     };
 
     html! {
-        <div class="card",>
-            <div class="card-content",>
-                { &task.name }
-            </div>
+        <div class=class,
+            draggable=can_edit.to_string(),
+            ondragstart=|e| {
+                // Firefox refuses to start a drag without any data attached.
+                if let Some(data_transfer) = e.data_transfer() {
+                    data_transfer.set_data("text/plain", &id.to_string());
+                }
+                Msg::DragStart(id)
+            },
+            ondragend=|_| Msg::DragEnd,
+            ondrop=|e| {
+                // Dropping onto a card puts the dragged one right before it.
+                e.prevent_default();
+                e.stop_propagation();
+                match dragging {
+                    Some(dragged) => Msg::MoveTask { id: dragged, to: status.clone(), position },
+                    None => Msg::DragEnd,
+                }
+            },>
+            { view_task_content(task, can_edit) }
             <footer class="card-footer",>
                 <div class="card-footer-item",>
-                    { &task.assignee }
+                    { view_assignee(task, model) }
                 </div>
                 <div class="card-footer-item",>
                     { format!("{}h", &task.estimate) }
@@ -166,40 +713,414 @@ fn view_task((idx, task): (usize, &Task)) -> Html<Model> {
                     {button_right}
                 </span>
             </footer>
+            <footer class="card-footer",>
+                <a class="card-footer-item", onclick=|_| Msg::OpenDetail(id),>{ "Details" }</a>
+                { view_archive_button(task, model) }
+                { view_delete_button(task, model) }
+            </footer>
+        </div>
+    }
+}
+
+/// The name and reason of a card, clicking them opens the edit form for those who may edit.
+fn view_task_content(task: &Task, can_edit: bool) -> Html<Model> {
+    let id = task.id;
+    match can_edit {
+        true => html! {
+            <div class="card-content", onclick=|_| Msg::EditTask(id),>
+                { &task.name }
+                { view_reason(task) }
+            </div>
+        },
+        false => html! {
+            <div class="card-content",>
+                { &task.name }
+                { view_reason(task) }
+            </div>
+        },
+    }
+}
+
+fn view_reason(task: &Task) -> Html<Model> {
+    match task.reason {
+        Some(ref reason) => html! {
+            <p class="help",>{ reason }</p>
+        },
+        None => html! {
+            <></>
+        },
+    }
+}
+
+fn view_archive_button(task: &Task, model: &Model) -> Html<Model> {
+    let id = task.id;
+    match model.can(Permission::EditTasks) && model.state.workflow.is_done(&task.status) {
+        true => html! {
+            <a class="card-footer-item", onclick=|_| Msg::ArchiveTask(id),>{ "Archive" }</a>
+        },
+        false => html! {
+            <></>
+        },
+    }
+}
+
+fn view_delete_button(task: &Task, model: &Model) -> Html<Model> {
+    let id = task.id;
+    match model.can(Permission::DeleteTasks) {
+        true => html! {
+            <a class="card-footer-item has-text-danger", onclick=|_| Msg::DeleteTask(id),>{ "Delete" }</a>
+        },
+        false => html! {
+            <></>
+        },
+    }
+}
+
+fn view_undo(model: &Model) -> Html<Model> {
+    match model.deleted {
+        Some((_, ref task)) => html! {
+            <div class="notification",>
+                { format!("\"{}\" was deleted. ", task.name) }
+                <a onclick=|_| Msg::UndoDelete,>{ "Undo" }</a>
+            </div>
+        },
+        None => html! {
+            <></>
+        },
+    }
+}
+
+fn view_error(model: &Model) -> Html<Model> {
+    match model.error {
+        Some(ref error) => html! {
+            <div class="notification is-danger",>
+                <button class="delete", onclick=|_| Msg::DismissError,></button>
+                { error }
+            </div>
+        },
+        None => html! {
+            <></>
+        },
+    }
+}
+
+fn view_limit_warning(model: &Model) -> Html<Model> {
+    let pending = match model.over_limit_move {
+        Some(ref pending) => pending,
+        None => return html! { <></> },
+    };
+
+    html! {
+        <div class="notification is-warning",>
+            <p>{ format!("\"{}\" is at its work-in-progress limit.", model.label_of(&pending.to)) }</p>
+            <div class="buttons",>
+                <button class="button is-small is-danger", onclick=|_| Msg::OverrideLimit,>{ "Move anyway" }</button>
+                <button class="button is-small", onclick=|_| Msg::CancelMove,>{ "Cancel" }</button>
+            </div>
+        </div>
+    }
+}
+
+fn view_reason_modal(model: &Model) -> Html<Model> {
+    let pending = match model.pending_move {
+        Some(ref pending) => pending,
+        None => return html! { <></> },
+    };
+
+    let title = match model.status_of(pending.id) {
+        Some(from) => format!(
+            "Why is this task moving from \"{}\" to \"{}\"?",
+            model.label_of(&from),
+            model.label_of(&pending.to)
+        ),
+        None => "Why is this task moving?".to_string(),
+    };
+
+    html! {
+        <div class="modal is-active",>
+            <div class="modal-background", onclick=|_| Msg::CancelMove,></div>
+            <div class="modal-card",>
+                <header class="modal-card-head",>
+                    <p class="modal-card-title",>{ title }</p>
+                </header>
+                <section class="modal-card-body",>
+                    <textarea class="textarea", value=&pending.reason, oninput=|e| Msg::UpdateReason(e.value),></textarea>
+                </section>
+                <footer class="modal-card-foot",>
+                    <button class="button is-primary", disabled=pending.reason.trim().is_empty(), onclick=|_| Msg::ConfirmMove,>{ "Move" }</button>
+                    <button class="button", onclick=|_| Msg::CancelMove,>{ "Cancel" }</button>
+                </footer>
+            </div>
+        </div>
+    }
+}
+
+/// The panel of the task picked by the route, see `Props::selected`.
+fn view_detail(model: &Model) -> Html<Model> {
+    let id = match model.selected {
+        Some(id) => id,
+        None => return html! { <></> },
+    };
+    let task = match model.state.task(id) {
+        Some(task) => task,
+        None => {
+            return html! {
+                <div class="notification is-warning",>
+                    <button class="delete", onclick=|_| Msg::CloseDetail,></button>
+                    { format!("There is no task {}.", id) }
+                </div>
+            };
+        }
+    };
+    let status = match model.state.workflow.column(&task.status) {
+        Some(column) => html! {
+            <span class="tag", style=format!("background-color: {}", column.color),>{ &column.label }</span>
+        },
+        None => html! {
+            <span class="tag",>{ &task.status }</span>
+        },
+    };
+    let archived = match task.archived {
+        true => html! {
+            <span class="tag is-dark",>{ "archived" }</span>
+        },
+        false => html! {
+            <></>
+        },
+    };
+
+    html! {
+        <div class="modal is-active",>
+            <div class="modal-background", onclick=|_| Msg::CloseDetail,></div>
+            <div class="modal-card",>
+                <header class="modal-card-head",>
+                    <p class="modal-card-title",>{ &task.name }</p>
+                    <button class="delete", onclick=|_| Msg::CloseDetail,></button>
+                </header>
+                <section class="modal-card-body",>
+                    <p>{ status }{ archived }</p>
+                    <p>{ view_assignee(task, model) }</p>
+                    <p>{ format!("Estimate: {}h", task.estimate) }</p>
+                    { view_reason(task) }
+                </section>
+                <footer class="modal-card-foot",>
+                    <button class="button", onclick=|_| Msg::CloseDetail,>{ "Close" }</button>
+                </footer>
+            </div>
+        </div>
+    }
+}
+
+fn view_team_filter(model: &Model) -> Html<Model> {
+    if model.state.teams.is_empty() {
+        return html! { <></> };
+    }
+    let selected = model.team_filter.map(|id| id.to_string()).unwrap_or_default();
+
+    html! {
+        <div class="field",>
+            <div class="select is-small",>
+                <select value=selected, onchange=|e| Msg::FilterTeam(e),>
+                    <option value="",>{ "All teams" }</option>
+                    { for model.state.teams.iter().map(view_team_option) }
+                </select>
+            </div>
+        </div>
+    }
+}
+
+fn view_workloads(model: &Model) -> Html<Model> {
+    let state = &model.state;
+    let team = model.team_filter.and_then(|id| state.team(id));
+    let users = state
+        .users
+        .iter()
+        .filter(|user| team.map_or(true, |team| team.has_member(user.id)));
+
+    html! {
+        <div class="columns is-multiline workloads",>
+            { for users.map(|user| view_workload(user, state.workload(user.id))) }
+        </div>
+    }
+}
+
+fn view_workload(user: &User, load: u32) -> Html<Model> {
+    let overloaded = load > user.capacity;
+    let (class, progress) = if overloaded {
+        ("column is-3 has-text-danger", "progress is-small is-danger")
+    } else {
+        ("column is-3", "progress is-small is-success")
+    };
+
+    html! {
+        <div class=class,>
+            <AvatarModel: user=Some(user.clone()),/>
+            { format!("{} {}/{}h", user.full_name(), load, user.capacity) }
+            <progress class=progress, value=load.to_string(), max=user.capacity.to_string(),></progress>
+        </div>
+    }
+}
+
+fn view_assignee(task: &Task, model: &Model) -> Html<Model> {
+    let team = task.team.and_then(|id| model.state.team(id));
+    match task.assignee.and_then(|id| model.state.users.iter().find(|user| user.id == id)) {
+        Some(user) => html! {
+            <span>
+                <AvatarModel: user=Some(user.clone()),/>
+                { user.full_name() }
+                { view_team_tag(team) }
+            </span>
+        },
+        None => html! {
+            <span class="has-text-grey",>
+                { "Unassigned" }
+                { view_team_tag(team) }
+            </span>
+        },
+    }
+}
+
+fn view_team_tag(team: Option<&Team>) -> Html<Model> {
+    match team {
+        Some(team) => html! {
+            <span class="tag is-light team-tag",>{ &team.name }</span>
+        },
+        None => html! {
+            <></>
+        },
+    }
+}
+
+fn view_edit_form(task: &Task, model: &Model) -> Html<Model> {
+    html! {
+        <div class="card",>
+            <div class="card-content",>
+                <div class="columns is-multiline",>
+                    { view_task_inputs(task, &model.state, EDIT_TASK_FORM) }
+                </div>
+            </div>
+            <footer class="card-footer",>
+                <a class="card-footer-item", onclick=|_| Msg::UpdateTask,>{ "Save" }</a>
+                <a class="card-footer-item", onclick=|_| Msg::CancelEdit,>{ "Cancel" }</a>
+            </footer>
         </div>
     }
 }
 
 fn view_header(model: &Model) -> Html<Model> {
+    if !model.can(Permission::EditTasks) {
+        return html! { <></> };
+    }
+
     html! {
         <div class="columns",>
-            <div class="column is-half",>
-                <input class="input", type="text", value=&model.new_task.name, oninput=|e| Msg::UpdateNewTaskName(e.value), />
+            { view_task_inputs(&model.state.new_task, &model.state, NEW_TASK_FORM) }
+
+            <div class="column",>
+                <button class="button is-fullwidth", onclick=|_| Msg::NewTask,>{ "+" }</button>
+            </div>
+        </div>
+    }
+}
+
+fn view_task_inputs(task: &Task, state: &State, form: TaskForm) -> Html<Model> {
+    html! {
+        <>
+            <div class="column is-one-third",>
+                <input class="input", type="text", value=&task.name, oninput=|e| (form.name)(e.value), />
             </div>
 
             <div class="column",>
-                {view_assignee_select(model)}
+                {view_assignee_select(task, state, form)}
             </div>
 
             <div class="column",>
-                <input class="input", type="text", value=&model.new_task.estimate, oninput=|e| Msg::UpdateNewTaskMandays(e.value), />
+                {view_team_select(task, state, form)}
             </div>
 
             <div class="column",>
-                <button class="button is-fullwidth", onclick=|_| Msg::NewTask,>{ "+" }</button>
+                <input class="input", type="text", value=&task.estimate, oninput=|e| (form.estimate)(e.value), />
+            </div>
+        </>
+    }
+}
+
+fn view_assignee_select(task: &Task, state: &State, form: TaskForm) -> Html<Model> {
+    let users = &state.users;
+    let selected = task.assignee.map(|id| id.to_string()).unwrap_or_default();
+    let assignee = task
+        .assignee
+        .and_then(|id| users.iter().find(|user| user.id == id))
+        .cloned();
+
+    html! {
+        <div class="field has-addons",>
+            <div class="control",>
+                <span class="button is-static",>
+                    <AvatarModel: user=assignee,/>
+                </span>
+            </div>
+            <div class="control is-expanded",>
+                <div class="select is-fullwidth",>
+                    <select value=selected, onchange=|e| (form.assignee)(e),>
+                        <option value="",>{ "Unassigned" }</option>
+                        { for users.iter().map(|user| view_assignee_option(user, projected_load(state, user, task))) }
+                    </select>
+                </div>
             </div>
         </div>
     }
 }
 
-fn view_assignee_select(model: &Model) -> Html<Model> {
+fn view_assignee_option(user: &User, load: u32) -> Html<Model> {
+    let (class, label) = if load > user.capacity {
+        (
+            "has-text-danger",
+            format!("⚠ {} ({}/{}h, overloaded)", user.label(), load, user.capacity),
+        )
+    } else {
+        ("", format!("{} ({}/{}h)", user.label(), load, user.capacity))
+    };
+
+    html! {
+        <option class=class, value=user.id.to_string(),>{ label }</option>
+    }
+}
+
+fn view_team_select(task: &Task, state: &State, form: TaskForm) -> Html<Model> {
+    let selected = task.team.map(|id| id.to_string()).unwrap_or_default();
+
     html! {
         <div class="select is-fullwidth",>
-            <select value=&model.new_task.assignee, onchange=|e| Msg::UpdateNewTaskAssignee(e),>
-                <option value="🐱",>{ "🐱" }</option>
-                <option value="🐶",>{ "🐶" }</option>
-                <option value="🐹",>{ "🐹" }</option>
+            <select value=selected, onchange=|e| (form.team)(e),>
+                <option value="",>{ "No team" }</option>
+                { for state.teams.iter().map(view_team_option) }
             </select>
         </div>
     }
 }
+
+fn view_team_option(team: &Team) -> Html<Model> {
+    html! {
+        <option value=team.id.to_string(),>{ &team.name }</option>
+    }
+}
+
+/// The user's workload if `task` were assigned to them, counting the task once
+/// even when it already is theirs.
+fn projected_load(state: &State, user: &User, task: &Task) -> u32 {
+    let counted = state
+        .task(task.id)
+        .filter(|existing| {
+            existing.assignee == Some(user.id)
+                && !existing.archived
+                && !state.workflow.is_done(&existing.status)
+        })
+        .map_or(0, |existing| existing.estimate);
+    state.workload(user.id).saturating_sub(counted).saturating_add(task.estimate)
+}
+
+/// Select values are user or team ids, the empty value stands for none.
+fn parse_id(value: &str) -> Option<u32> {
+    u32::from_str_radix(value, 10).ok()
+}
@@ -1,3 +1,8 @@
 pub mod app;
+pub mod archive;
+pub mod avatar;
 pub mod board;
+pub mod team_list;
 pub mod user_list;
+pub mod user_profile;
+pub mod workflow_editor;
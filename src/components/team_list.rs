@@ -0,0 +1,243 @@
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+use crate::components::avatar::Model as AvatarModel;
+use crate::models::role::Permission;
+use crate::models::team::{Team, TeamId};
+use crate::models::user::{User, UserId};
+use crate::store::{Action, Request, State, Store};
+
+pub enum Msg {
+    StateChanged(State),
+    UpdateName(String),
+    ToggleMember(UserId),
+    UpdateLead(yew::html::ChangeData),
+    EditTeam(TeamId),
+    SaveTeam,
+    CancelEdit,
+    RemoveTeam(TeamId),
+}
+
+pub struct Model {
+    store: Box<Bridge<Store>>,
+    state: State,
+    /// The team being added (id `0`) or edited.
+    form: Team,
+    error: Option<String>,
+}
+
+fn empty_form() -> Team {
+    Team::new(0, "".to_string())
+}
+
+impl Model {
+    fn find_user(&self, id: UserId) -> Option<&User> {
+        self.state.users.iter().find(|user| user.id == id)
+    }
+
+    fn can_manage(&self) -> bool {
+        self.state.current_role().allows(Permission::ManageUsers)
+    }
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|state: State| Msg::StateChanged(state));
+        let mut store = Store::bridge(callback);
+        store.send(Request::GetState);
+
+        Model {
+            store,
+            state: State::default(),
+            form: empty_form(),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let changes_teams = match msg {
+            Msg::StateChanged(_) | Msg::CancelEdit => false,
+            _ => true,
+        };
+        if changes_teams && !self.can_manage() {
+            self.error = Some("Only admins can change teams.".to_string());
+            return true;
+        }
+
+        match msg {
+            Msg::StateChanged(state) => {
+                self.state = state;
+            }
+
+            Msg::UpdateName(val) => {
+                self.form.name = val;
+            }
+
+            Msg::ToggleMember(id) => {
+                self.form.toggle_member(id);
+            }
+
+            Msg::UpdateLead(val) => {
+                if let yew::html::ChangeData::Select(v) = &val {
+                    self.form.lead = u32::from_str_radix(&v.raw_value(), 10).ok();
+                }
+            }
+
+            Msg::EditTeam(id) => {
+                if let Some(team) = self.state.team(id).cloned() {
+                    self.form = team;
+                    self.error = None;
+                }
+            }
+
+            Msg::SaveTeam => {
+                if let Err(error) = self.form.validate(&self.state.teams) {
+                    self.error = Some(error);
+                    return true;
+                }
+                let mut team = std::mem::replace(&mut self.form, empty_form());
+                team.name = team.name.trim().to_string();
+                let action = if team.id == 0 {
+                    Action::AddTeam(team)
+                } else {
+                    Action::UpdateTeam(team)
+                };
+                self.store.send(Request::Dispatch(action));
+                self.error = None;
+            }
+
+            Msg::CancelEdit => {
+                self.form = empty_form();
+                self.error = None;
+            }
+
+            Msg::RemoveTeam(id) => {
+                if self.form.id == id {
+                    self.form = empty_form();
+                }
+                self.store.send(Request::Dispatch(Action::RemoveTeam(id)));
+            }
+        }
+        true
+    }
+}
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        html! {
+            <div class="container",>
+                <h2>{ "Teams" }</h2>
+                { for self.state.teams.iter().map(|team| view_team(team, self)) }
+                { view_form(self) }
+            </div>
+        }
+    }
+}
+
+fn view_team(team: &Team, model: &Model) -> Html<Model> {
+    let id = team.id;
+    let buttons = match model.can_manage() {
+        true => html! {
+            <>
+                <a class="button is-small is-white", onclick=|_| Msg::EditTeam(id),>{ "Edit" }</a>
+                <a class="button is-small is-white has-text-danger", onclick=|_| Msg::RemoveTeam(id),>{ "Remove" }</a>
+            </>
+        },
+        false => html! {
+            <></>
+        },
+    };
+    let lead = match team.lead.and_then(|id| model.find_user(id)) {
+        Some(lead) => format!("Lead: {}", lead.full_name()),
+        None => "No lead".to_string(),
+    };
+
+    html! {
+        <div class="box",>
+            <h3>
+                { &team.name }
+                { buttons }
+            </h3>
+            <p class="help",>{ lead }</p>
+            <div>
+                { for team.members.iter().filter_map(|id| model.find_user(*id)).map(view_member) }
+            </div>
+        </div>
+    }
+}
+
+fn view_member(user: &User) -> Html<Model> {
+    html! {
+        <AvatarModel: user=Some(user.clone()),/>
+    }
+}
+
+fn view_form(model: &Model) -> Html<Model> {
+    if !model.can_manage() {
+        return html! { <></> };
+    }
+
+    let form = &model.form;
+    let (title, submit) = if form.id == 0 {
+        ("Add a team", "Add")
+    } else {
+        ("Edit team", "Save")
+    };
+    let selected = form.lead.map(|id| id.to_string()).unwrap_or_default();
+    let error = match model.error {
+        Some(ref error) => html! {
+            <p class="help is-danger",>{ error }</p>
+        },
+        None => html! {
+            <></>
+        },
+    };
+
+    html! {
+        <div class="box",>
+            <h3>{ title }</h3>
+            <div class="columns",>
+                <div class="column",>
+                    <input class="input", type="text", placeholder="Name", value=&form.name, oninput=|e| Msg::UpdateName(e.value), />
+                </div>
+                <div class="column",>
+                    <div class="select is-fullwidth",>
+                        <select value=selected, onchange=|e| Msg::UpdateLead(e),>
+                            <option value="",>{ "No lead" }</option>
+                            { for form.members.iter().filter_map(|id| model.find_user(*id)).map(view_lead_option) }
+                        </select>
+                    </div>
+                </div>
+                <div class="column is-narrow",>
+                    <button class="button is-primary", onclick=|_| Msg::SaveTeam,>{ submit }</button>
+                    <button class="button", onclick=|_| Msg::CancelEdit,>{ "Cancel" }</button>
+                </div>
+            </div>
+            <div class="field is-grouped is-grouped-multiline",>
+                { for model.state.users.iter().map(|user| view_member_checkbox(user, form)) }
+            </div>
+            { error }
+        </div>
+    }
+}
+
+fn view_lead_option(user: &User) -> Html<Model> {
+    html! {
+        <option value=user.id.to_string(),>{ user.label() }</option>
+    }
+}
+
+fn view_member_checkbox(user: &User, form: &Team) -> Html<Model> {
+    let id = user.id;
+    html! {
+        <div class="control",>
+            <label class="checkbox",>
+                <input type="checkbox", checked=form.has_member(id), onclick=|_| Msg::ToggleMember(id), />
+                <AvatarModel: user=Some(user.clone()),/>
+                { user.full_name() }
+            </label>
+        </div>
+    }
+}
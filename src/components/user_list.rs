@@ -1,32 +1,284 @@
-use yew::prelude::{Component, ComponentLink, Html, Renderable, ShouldRender};
+use stdweb::web::{File, IBlob};
+use stdweb::Once;
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
 
-use crate::models::user::{Avatar, User};
+use crate::components::avatar::Model as AvatarModel;
+use crate::models::role::{Permission, Role, ROLES};
+use crate::models::user::{Avatar, User, UserId};
+use crate::router;
+use crate::routes::AppRoute;
+use crate::store::{Action, Request, State, Store};
 
-pub enum Msg {}
+/// Uploaded pictures are kept in local storage as `data:` URLs, so they have to stay small.
+const MAX_AVATAR_BYTES: u64 = 100 * 1024;
+
+const LAST_ADMIN: &'static str = "There has to be at least one admin, make somebody else one first.";
+
+const EMOJI: [&'static str; 12] = [
+  "🐱", "🐶", "🐹", "🦊", "🐻", "🐼", "🐨", "🐯", "🦁", "🐸", "🐵", "🦉",
+];
+
+pub enum Msg {
+  StateChanged(State),
+  /// The router broadcasts every route change, the list has no use for them.
+  RouteChanged,
+  OpenProfile(UserId),
+  UpdateFirstName(String),
+  UpdateLastName(String),
+  UpdateCapacity(String),
+  UpdateRole(yew::html::ChangeData),
+  SetAvatar(Avatar),
+  UploadAvatar(yew::html::ChangeData),
+  AvatarLoaded(String),
+  EditUser(UserId),
+  SaveUser,
+  CancelEdit,
+  RemoveUser(UserId),
+  UpdateReassignTo(yew::html::ChangeData),
+  ConfirmRemove,
+  CancelRemove,
+}
+
+/// A user about to be removed, and who should take over their tasks.
+struct Removal {
+  id: UserId,
+  reassign_to: Option<UserId>,
+}
 
 pub struct Model {
-  users: Vec<User>,
+  link: ComponentLink<Model>,
+  store: Box<Bridge<Store>>,
+  router: Box<Bridge<router::Router<()>>>,
+  state: State,
+  /// The user being added (id `0`) or edited.
+  form: User,
+  error: Option<String>,
+  removal: Option<Removal>,
+  guard: router::ConfirmGuard,
+}
+
+fn empty_form() -> User {
+  User::new(0, "".to_string(), "".to_string(), Avatar::Initials)
+}
+
+impl Model {
+  /// Reads a picked file as a `data:` URL, which arrives as `Msg::AvatarLoaded`.
+  fn read_avatar(&mut self, file: File) {
+    let callback = self.link.send_back(Msg::AvatarLoaded);
+    let on_load = move |data_url: String| callback.emit(data_url);
+    js! { @(no_return)
+      var on_load = @{Once(on_load)};
+      var reader = new FileReader();
+      reader.onload = function() { on_load(reader.result); };
+      reader.readAsDataURL(@{file});
+    }
+  }
+
+  fn tasks_of(&self, id: UserId) -> usize {
+    self
+      .state
+      .tasks
+      .iter()
+      .filter(|task| task.assignee == Some(id))
+      .count()
+  }
+
+  fn find_user(&self, id: UserId) -> Option<&User> {
+    self.state.users.iter().find(|user| user.id == id)
+  }
+
+  fn can_manage(&self) -> bool {
+    self.state.current_role().allows(Permission::ManageUsers)
+  }
+
+  /// Whether the form holds anything that leaving the list would throw away.
+  fn unsaved_changes(&self) -> Option<String> {
+    let saved = self.find_user(self.form.id).cloned().unwrap_or_else(empty_form);
+    match self.form != saved {
+      true => Some("Discard the user you started editing?".to_string()),
+      false => None,
+    }
+  }
+
+  fn apply(&mut self, msg: Msg) -> ShouldRender {
+    if changes_users(&msg) && !self.can_manage() {
+      self.error = Some("Only admins can change users.".to_string());
+      return true;
+    }
+
+    match msg {
+      Msg::StateChanged(state) => {
+        self.state = state;
+      }
+
+      Msg::RouteChanged => return false,
+
+      Msg::OpenProfile(id) => {
+        self.router.send(router::Request::ChangeRoute(AppRoute::User(id).to_route()));
+        return false;
+      }
+
+      Msg::UpdateFirstName(val) => {
+        self.form.first_name = val;
+      }
+
+      Msg::UpdateLastName(val) => {
+        self.form.last_name = val;
+      }
+
+      Msg::UpdateCapacity(val) => {
+        if let Ok(v) = u32::from_str_radix(&val, 10) {
+          self.form.capacity = v;
+        }
+      }
+
+      Msg::UpdateRole(val) => {
+        if let yew::html::ChangeData::Select(v) = &val {
+          if let Some(role) = Role::from_key(&v.raw_value()) {
+            self.form.role = role;
+          }
+        }
+      }
+
+      Msg::SetAvatar(avatar) => {
+        self.form.avatar = avatar;
+      }
+
+      Msg::UploadAvatar(val) => {
+        if let yew::html::ChangeData::Files(files) = val {
+          if let Some(file) = files.iter().next() {
+            if file.len() > MAX_AVATAR_BYTES {
+              self.error = Some(format!("Pictures can be at most {} KB.", MAX_AVATAR_BYTES / 1024));
+              return true;
+            }
+            self.read_avatar(file);
+          }
+        }
+        return false;
+      }
+
+      Msg::AvatarLoaded(data_url) => {
+        self.form.avatar = Avatar::Image(data_url);
+      }
+
+      Msg::EditUser(id) => {
+        if let Some(user) = self.find_user(id).cloned() {
+          self.form = user;
+          self.error = None;
+        }
+      }
+
+      Msg::SaveUser => {
+        if let Err(error) = self.form.validate(&self.state.users) {
+          self.error = Some(error);
+          return true;
+        }
+        if self.state.would_leave_no_admin(self.form.id, Some(self.form.role)) {
+          self.error = Some(LAST_ADMIN.to_string());
+          return true;
+        }
+        let mut user = std::mem::replace(&mut self.form, empty_form());
+        user.first_name = user.first_name.trim().to_string();
+        user.last_name = user.last_name.trim().to_string();
+        let action = if user.id == 0 {
+          Action::AddUser(user)
+        } else {
+          Action::UpdateUser(user)
+        };
+        self.store.send(Request::Dispatch(action));
+        self.error = None;
+      }
+
+      Msg::CancelEdit => {
+        self.form = empty_form();
+        self.error = None;
+      }
+
+      Msg::RemoveUser(id) => {
+        self.removal = Some(Removal {
+          id,
+          reassign_to: None,
+        });
+      }
+
+      Msg::UpdateReassignTo(val) => {
+        if let (Some(removal), yew::html::ChangeData::Select(v)) = (self.removal.as_mut(), &val) {
+          removal.reassign_to = u32::from_str_radix(&v.raw_value(), 10).ok();
+        }
+      }
+
+      Msg::ConfirmRemove => {
+        if let Some(Removal { id, reassign_to }) = self.removal.take() {
+          if self.state.would_leave_no_admin(id, None) {
+            self.error = Some(LAST_ADMIN.to_string());
+            return true;
+          }
+          if self.form.id == id {
+            self.form = empty_form();
+          }
+          self.store.send(Request::Dispatch(Action::RemoveUser { id, reassign_to }));
+        }
+      }
+
+      Msg::CancelRemove => {
+        self.removal = None;
+      }
+    }
+    true
+  }
+}
+
+/// Whether a message changes users and so needs `Permission::ManageUsers`.
+fn changes_users(msg: &Msg) -> bool {
+  match msg {
+    Msg::StateChanged(_)
+    | Msg::RouteChanged
+    | Msg::OpenProfile(_)
+    | Msg::CancelEdit
+    | Msg::CancelRemove => false,
+    Msg::UpdateFirstName(_)
+    | Msg::UpdateLastName(_)
+    | Msg::UpdateCapacity(_)
+    | Msg::UpdateRole(_)
+    | Msg::SetAvatar(_)
+    | Msg::UploadAvatar(_)
+    | Msg::AvatarLoaded(_)
+    | Msg::EditUser(_)
+    | Msg::SaveUser
+    | Msg::RemoveUser(_)
+    | Msg::UpdateReassignTo(_)
+    | Msg::ConfirmRemove => true,
+  }
 }
 
 impl Component for Model {
   type Message = Msg;
   type Properties = ();
 
-  fn create(_: Self::Properties, _: ComponentLink<Self>) -> Self {
+  fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+    let callback = link.send_back(|state: State| Msg::StateChanged(state));
+    let mut store = Store::bridge(callback);
+    store.send(Request::GetState);
+
+    let router = router::Router::bridge(link.send_back(|_: router::Route<()>| Msg::RouteChanged));
+
     Model {
-      users: vec![
-        User::new("John".to_string(), "Doe".to_string(), Avatar::Empty),
-        User::new(
-          "Jane".to_string(),
-          "Smith".to_string(),
-          Avatar::Emoji("🐶".to_string()),
-        ),
-      ],
+      link,
+      store,
+      router,
+      state: State::default(),
+      form: empty_form(),
+      error: None,
+      removal: None,
+      guard: router::ConfirmGuard::default(),
     }
   }
 
-  fn update(&mut self, _: Self::Message) -> ShouldRender {
-    true
+  fn update(&mut self, msg: Self::Message) -> ShouldRender {
+    let render = self.apply(msg);
+    let unsaved = self.unsaved_changes();
+    self.guard.update(&mut self.router, unsaved);
+    render
   }
 }
 
@@ -35,26 +287,191 @@ impl Renderable<Model> for Model {
     html! {
       <div class="container",>
         <h2>{ "Users" }</h2>
-        { view_list(&self.users) }
+        { view_list(&self) }
+        { view_removal(&self) }
+        { view_form(&self) }
       </div>
     }
   }
 }
 
-fn view_item((idx, user): (usize, &User)) -> Html<Model> {
+fn view_item((idx, user): (usize, &User), can_manage: bool) -> Html<Model> {
+  let id = user.id;
   html! {
     <li>
       <span>{ idx + 1 }</span>
-      <span>{ &user.full_name() }</span>
-      <span>{ &user.avatar }</span>
+      <AvatarModel: user=Some(user.clone()),/>
+      <a onclick=|_| Msg::OpenProfile(id),>{ &user.full_name() }</a>
+      <span class="tag is-light",>{ user.role }</span>
+      { view_item_buttons(id, can_manage) }
     </li>
   }
 }
 
-fn view_list(users: &Vec<User>) -> Html<Model> {
+fn view_item_buttons(id: UserId, can_manage: bool) -> Html<Model> {
+  if !can_manage {
+    return html! { <></> };
+  }
+
+  html! {
+    <>
+      <a class="button is-small is-white", onclick=|_| Msg::EditUser(id),>{ "Edit" }</a>
+      <a class="button is-small is-white has-text-danger", onclick=|_| Msg::RemoveUser(id),>{ "Remove" }</a>
+    </>
+  }
+}
+
+fn view_list(model: &Model) -> Html<Model> {
+  let can_manage = model.can_manage();
   html! {
     <ul>
-      { for users.iter().enumerate().map(view_item) }
+      { for model.state.users.iter().enumerate().map(|item| view_item(item, can_manage)) }
     </ul>
   }
 }
+
+fn view_form(model: &Model) -> Html<Model> {
+  if !model.can_manage() {
+    return match model.error {
+      Some(ref error) => html! {
+        <p class="help is-danger",>{ error }</p>
+      },
+      None => html! {
+        <></>
+      },
+    };
+  }
+
+  let form = &model.form;
+  let (title, submit) = if form.id == 0 {
+    ("Add a user", "Add")
+  } else {
+    ("Edit user", "Save")
+  };
+  let error = match model.error {
+    Some(ref error) => html! {
+      <p class="help is-danger",>{ error }</p>
+    },
+    None => html! {
+      <></>
+    },
+  };
+
+  html! {
+    <div class="box",>
+      <h3>{ title }</h3>
+      <div class="columns",>
+        <div class="column",>
+          <input class="input", type="text", placeholder="First name", value=&form.first_name, oninput=|e| Msg::UpdateFirstName(e.value), />
+        </div>
+        <div class="column",>
+          <input class="input", type="text", placeholder="Last name", value=&form.last_name, oninput=|e| Msg::UpdateLastName(e.value), />
+        </div>
+        <div class="column is-2",>
+          <div class="field has-addons",>
+            <div class="control is-expanded",>
+              <input class="input", type="number", min="0", value=&form.capacity, oninput=|e| Msg::UpdateCapacity(e.value), />
+            </div>
+            <div class="control",>
+              <span class="button is-static",>{ "h/week" }</span>
+            </div>
+          </div>
+        </div>
+        <div class="column is-narrow",>
+          <div class="select",>
+            <select value=form.role.key(), onchange=|e| Msg::UpdateRole(e),>
+              { for ROLES.iter().cloned().map(view_role_option) }
+            </select>
+          </div>
+        </div>
+        <div class="column is-narrow",>
+          <button class="button is-primary", onclick=|_| Msg::SaveUser,>{ submit }</button>
+          <button class="button", onclick=|_| Msg::CancelEdit,>{ "Cancel" }</button>
+        </div>
+      </div>
+      { view_avatar_picker(form) }
+      { error }
+    </div>
+  }
+}
+
+fn view_removal(model: &Model) -> Html<Model> {
+  let removal = match model.removal {
+    Some(ref removal) => removal,
+    None => return html! { <></> },
+  };
+  let user = match model.find_user(removal.id) {
+    Some(user) => user,
+    None => return html! { <></> },
+  };
+  let selected = removal
+    .reassign_to
+    .map(|id| id.to_string())
+    .unwrap_or_default();
+
+  html! {
+    <div class="notification is-warning",>
+      <p>{ format!("Remove {}? They have {} task(s).", user.full_name(), model.tasks_of(user.id)) }</p>
+      <div class="field has-addons",>
+        <div class="control",>
+          <div class="select",>
+            <select value=selected, onchange=|e| Msg::UpdateReassignTo(e),>
+              <option value="",>{ "Leave their tasks unassigned" }</option>
+              { for model.state.users.iter().filter(|other| other.id != user.id).map(view_reassign_option) }
+            </select>
+          </div>
+        </div>
+        <div class="control",>
+          <button class="button is-danger", onclick=|_| Msg::ConfirmRemove,>{ "Remove" }</button>
+        </div>
+        <div class="control",>
+          <button class="button", onclick=|_| Msg::CancelRemove,>{ "Cancel" }</button>
+        </div>
+      </div>
+    </div>
+  }
+}
+
+fn view_role_option(role: Role) -> Html<Model> {
+  html! {
+    <option value=role.key(),>{ role }</option>
+  }
+}
+
+fn view_reassign_option(user: &User) -> Html<Model> {
+  html! {
+    <option value=user.id.to_string(),>{ format!("Reassign to {}", user.label()) }</option>
+  }
+}
+
+fn view_avatar_picker(form: &User) -> Html<Model> {
+  html! {
+    <div class="field is-grouped is-grouped-multiline",>
+      <div class="control",>
+        <AvatarModel: user=Some(form.clone()),/>
+      </div>
+      <div class="control",>
+        <button class="button is-small", onclick=|_| Msg::SetAvatar(Avatar::Initials),>{ "Initials" }</button>
+      </div>
+      <div class="control buttons has-addons emoji-picker",>
+        { for EMOJI.iter().cloned().map(view_emoji_button) }
+      </div>
+      <div class="control",>
+        <div class="file is-small",>
+          <label class="file-label",>
+            <input class="file-input", type="file", accept="image/*", onchange=|e| Msg::UploadAvatar(e), />
+            <span class="file-cta",>
+              <span class="file-label",>{ "Upload a picture…" }</span>
+            </span>
+          </label>
+        </div>
+      </div>
+    </div>
+  }
+}
+
+fn view_emoji_button(emoji: &'static str) -> Html<Model> {
+  html! {
+    <button class="button is-small is-white", onclick=|_| Msg::SetAvatar(Avatar::Emoji(emoji.to_string())),>{ emoji }</button>
+  }
+}
@@ -0,0 +1,207 @@
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+use crate::components::avatar::Model as AvatarModel;
+use crate::models::role::Permission;
+use crate::models::task::{Task, TaskId};
+use crate::models::user::{User, UserId};
+use crate::models::workflow::Column;
+use crate::store::{Action, Request, State, Store};
+
+/// How many completed tasks the profile lists.
+const RECENTLY_COMPLETED: usize = 5;
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Props {
+    pub user_id: UserId,
+}
+
+pub enum Msg {
+    StateChanged(State),
+    Reassign(TaskId, yew::html::ChangeData),
+}
+
+pub struct Model {
+    store: Box<Bridge<Store>>,
+    state: State,
+    user_id: UserId,
+}
+
+impl Model {
+    fn user(&self) -> Option<&User> {
+        self.state.users.iter().find(|user| user.id == self.user_id)
+    }
+
+    /// Tasks assigned to the user that are still on the board.
+    fn assigned(&self) -> impl Iterator<Item = &Task> {
+        let user_id = self.user_id;
+        self.state
+            .tasks
+            .iter()
+            .filter(move |task| !task.archived && task.assignee == Some(user_id))
+    }
+
+    fn open_tasks(&self) -> Vec<&Task> {
+        let workflow = &self.state.workflow;
+        self.assigned()
+            .filter(|task| !workflow.is_done(&task.status))
+            .collect()
+    }
+
+    fn recently_completed(&self) -> Vec<&Task> {
+        let user_id = self.user_id;
+        let workflow = &self.state.workflow;
+        let mut done: Vec<&Task> = self
+            .state
+            .tasks
+            .iter()
+            .filter(|task| task.assignee == Some(user_id) && workflow.is_done(&task.status))
+            .collect();
+        done.sort_by(|a, b| {
+            let a = a.completed_at.unwrap_or(0.0);
+            let b = b.completed_at.unwrap_or(0.0);
+            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        done.truncate(RECENTLY_COMPLETED);
+        done
+    }
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|state: State| Msg::StateChanged(state));
+        let mut store = Store::bridge(callback);
+        store.send(Request::GetState);
+
+        Model {
+            store,
+            state: State::default(),
+            user_id: props.user_id,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::StateChanged(state) => {
+                self.state = state;
+                true
+            }
+
+            Msg::Reassign(id, val) => {
+                if !self.state.current_role().allows(Permission::EditTasks) {
+                    return false;
+                }
+                if let yew::html::ChangeData::Select(v) = &val {
+                    let assignee = u32::from_str_radix(&v.raw_value(), 10).ok();
+                    self.store.send(Request::Dispatch(Action::AssignTask(id, assignee)));
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.user_id = props.user_id;
+        true
+    }
+}
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        let user = match self.user() {
+            Some(user) => user,
+            None => {
+                return html! {
+                    <div class="container",>
+                        { format!("Not found: user {}", self.user_id) }
+                    </div>
+                };
+            }
+        };
+
+        let open_tasks = self.open_tasks();
+        let remaining = open_tasks.iter().map(|task| task.estimate).fold(0, u32::saturating_add);
+
+        html! {
+            <div class="container",>
+                <h2 class="title",>
+                    <AvatarModel: user=Some(user.clone()),/>
+                    { user.full_name() }
+                </h2>
+                <p class="subtitle",>
+                    { format!("{} open task(s), {}h remaining of {}h capacity", open_tasks.len(), remaining, user.capacity) }
+                </p>
+                { for self.state.workflow.columns.iter().map(|column| view_group(column, &open_tasks, self)) }
+                <h3>{ "Recently completed" }</h3>
+                { view_completed(&self.recently_completed()) }
+            </div>
+        }
+    }
+}
+
+fn view_group(column: &Column, open_tasks: &[&Task], model: &Model) -> Html<Model> {
+    let tasks: Vec<&Task> = open_tasks
+        .iter()
+        .cloned()
+        .filter(|task| task.status == column.key)
+        .collect();
+    if tasks.is_empty() {
+        return html! { <></> };
+    }
+
+    html! {
+        <>
+            <h3>
+                <span class="tag", style=format!("background-color: {}", column.color),>{ &column.label }</span>
+            </h3>
+            <table class="table is-fullwidth",>
+                <tbody>
+                    { for tasks.iter().map(|task| view_open_task(task, model)) }
+                </tbody>
+            </table>
+        </>
+    }
+}
+
+fn view_open_task(task: &Task, model: &Model) -> Html<Model> {
+    let id = task.id;
+    let selected = task.assignee.map(|id| id.to_string()).unwrap_or_default();
+    let can_edit = model.state.current_role().allows(Permission::EditTasks);
+
+    html! {
+        <tr>
+            <td>{ &task.name }</td>
+            <td>{ format!("{}h", &task.estimate) }</td>
+            <td>
+                <div class="select is-small",>
+                    <select value=selected, disabled=!can_edit, onchange=|e| Msg::Reassign(id, e),>
+                        <option value="",>{ "Unassigned" }</option>
+                        { for model.state.users.iter().map(view_user_option) }
+                    </select>
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn view_user_option(user: &User) -> Html<Model> {
+    html! {
+        <option value=user.id.to_string(),>{ user.label() }</option>
+    }
+}
+
+fn view_completed(tasks: &[&Task]) -> Html<Model> {
+    if tasks.is_empty() {
+        return html! {
+            <p>{ "Nothing completed yet." }</p>
+        };
+    }
+
+    html! {
+        <ul>
+            { for tasks.iter().map(|task| html! { <li>{ format!("{} ({}h)", task.name, task.estimate) }</li> }) }
+        </ul>
+    }
+}
@@ -0,0 +1,306 @@
+use yew::prelude::{Bridge, Bridged, Component, ComponentLink, Html, Renderable, ShouldRender};
+
+use crate::models::role::{Permission, Role};
+use crate::models::workflow::{Column, Rule, Workflow};
+use crate::store::{Action, Request, State, Store};
+
+const NEW_COLUMN_COLOR: &'static str = "#b0bec5";
+
+pub enum Msg {
+    StateChanged(State),
+    UpdateNewLabel(String),
+    AddColumn,
+    Rename(usize, String),
+    Recolor(usize, String),
+    UpdateLimit(usize, String),
+    ToggleBlockFullColumns,
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+    SetRule(usize, usize, yew::html::ChangeData),
+    Reset,
+}
+
+pub struct Model {
+    store: Box<Bridge<Store>>,
+    /// A working copy of the store's workflow, sent back after every change.
+    workflow: Workflow,
+    new_label: String,
+    /// The role of the signed in user, only admins may change the workflow.
+    role: Role,
+}
+
+impl Model {
+    fn can_manage(&self) -> bool {
+        self.role.allows(Permission::ManageWorkflow)
+    }
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|state: State| Msg::StateChanged(state));
+        let mut store = Store::bridge(callback);
+        store.send(Request::GetState);
+
+        Model {
+            store,
+            workflow: Workflow::default(),
+            new_label: "".to_string(),
+            role: Role::Viewer,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let changes_workflow = match msg {
+            Msg::StateChanged(_) => false,
+            _ => true,
+        };
+        // The controls are disabled for everybody else, this keeps a stray message from saving anyway.
+        if changes_workflow && !self.can_manage() {
+            return false;
+        }
+
+        match msg {
+            Msg::StateChanged(state) => {
+                self.role = state.current_role();
+                self.workflow = state.workflow;
+                return true;
+            }
+
+            Msg::UpdateNewLabel(val) => {
+                self.new_label = val;
+                return true;
+            }
+
+            Msg::AddColumn => {
+                if self.new_label.trim().is_empty() {
+                    return false;
+                }
+                self.workflow.add_column(&self.new_label, NEW_COLUMN_COLOR);
+                self.new_label = "".to_string();
+            }
+
+            Msg::Rename(idx, label) => {
+                if let Some(column) = self.workflow.columns.get_mut(idx) {
+                    column.label = label;
+                }
+            }
+
+            Msg::Recolor(idx, color) => {
+                if let Some(column) = self.workflow.columns.get_mut(idx) {
+                    column.color = color;
+                }
+            }
+
+            Msg::UpdateLimit(idx, val) => {
+                let limit = if val.trim().is_empty() {
+                    None
+                } else {
+                    match usize::from_str_radix(val.trim(), 10) {
+                        Ok(limit) => Some(limit),
+                        Err(_) => return false,
+                    }
+                };
+                if let Some(column) = self.workflow.columns.get_mut(idx) {
+                    column.limit = limit;
+                }
+            }
+
+            Msg::ToggleBlockFullColumns => {
+                self.workflow.block_full_columns = !self.workflow.block_full_columns;
+            }
+
+            Msg::MoveUp(idx) => {
+                if idx > 0 {
+                    self.workflow.move_column(idx, idx - 1);
+                }
+            }
+
+            Msg::MoveDown(idx) => self.workflow.move_column(idx, idx + 1),
+
+            Msg::Remove(idx) => self.workflow.remove_column(idx),
+
+            Msg::SetRule(from, to, val) => {
+                let rule = match &val {
+                    yew::html::ChangeData::Select(v) => match v.raw_value().as_str() {
+                        "allowed" => Some(Rule::Allowed),
+                        "reason" => Some(Rule::NeedsReason),
+                        _ => None,
+                    },
+                    _ => return false,
+                };
+                let keys = (self.workflow.columns.get(from), self.workflow.columns.get(to));
+                if let (Some(from), Some(to)) = keys {
+                    let (from, to) = (from.key.clone(), to.key.clone());
+                    self.workflow.set_rule(&from, &to, rule);
+                }
+            }
+
+            Msg::Reset => {
+                self.workflow = Workflow::default();
+            }
+        }
+        let workflow = self.workflow.clone();
+        self.store.send(Request::Dispatch(Action::SetWorkflow(workflow)));
+        true
+    }
+}
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        let editable = self.can_manage();
+        let count = self.workflow.columns.len();
+
+        html! {
+            <div class="container",>
+                <h2>{ "Workflow" }</h2>
+                { view_read_only_notice(editable) }
+                <table class="table is-fullwidth",>
+                    <thead>
+                        <tr>
+                            <th>{ "Color" }</th>
+                            <th>{ "Label" }</th>
+                            <th>{ "Key" }</th>
+                            <th>{ "WIP limit" }</th>
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for self.workflow.columns.iter().enumerate().map(|(idx, column)| view_column(idx, column, count, editable)) }
+                    </tbody>
+                </table>
+                { view_add_form(&self) }
+                <label class="checkbox",>
+                    <input type="checkbox", disabled=!editable, checked=self.workflow.block_full_columns, onclick=|_| Msg::ToggleBlockFullColumns, />
+                    { " Ask before moving tasks into a column that reached its limit" }
+                </label>
+                { view_transitions(&self.workflow, editable) }
+                { view_reset_button(editable) }
+            </div>
+        }
+    }
+}
+
+fn view_read_only_notice(editable: bool) -> Html<Model> {
+    match editable {
+        true => html! {
+            <></>
+        },
+        false => html! {
+            <p class="help",>{ "Only admins can change the workflow." }</p>
+        },
+    }
+}
+
+fn view_reset_button(editable: bool) -> Html<Model> {
+    match editable {
+        true => html! {
+            <button class="button is-text", onclick=|_| Msg::Reset,>{ "Reset to default columns" }</button>
+        },
+        false => html! {
+            <></>
+        },
+    }
+}
+
+fn view_column(idx: usize, column: &Column, count: usize, editable: bool) -> Html<Model> {
+    let limit = column.limit.map(|limit| limit.to_string()).unwrap_or_default();
+
+    html! {
+        <tr>
+            <td>
+                <input class="input", type="color", disabled=!editable, value=&column.color, oninput=|e| Msg::Recolor(idx, e.value), />
+            </td>
+            <td>
+                <input class="input", type="text", disabled=!editable, value=&column.label, oninput=|e| Msg::Rename(idx, e.value), />
+            </td>
+            <td>
+                <span class="tag",>{ &column.key }</span>
+            </td>
+            <td>
+                <input class="input", type="number", min="0", placeholder="none", disabled=!editable, value=limit, oninput=|e| Msg::UpdateLimit(idx, e.value), />
+            </td>
+            <td>
+                <div class="buttons has-addons",>
+                    <button class="button is-small", disabled=!editable || idx == 0, onclick=|_| Msg::MoveUp(idx),>{ "▲" }</button>
+                    <button class="button is-small", disabled=!editable || idx + 1 == count, onclick=|_| Msg::MoveDown(idx),>{ "▼" }</button>
+                    <button class="button is-small is-danger", disabled=!editable || count == 1, onclick=|_| Msg::Remove(idx),>{ "✕" }</button>
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+fn view_add_form(model: &Model) -> Html<Model> {
+    if !model.can_manage() {
+        return html! { <></> };
+    }
+
+    html! {
+        <div class="field has-addons",>
+            <div class="control is-expanded",>
+                <input class="input", type="text", placeholder="New column", value=&model.new_label, oninput=|e| Msg::UpdateNewLabel(e.value), />
+            </div>
+            <div class="control",>
+                <button class="button", onclick=|_| Msg::AddColumn,>{ "+" }</button>
+            </div>
+        </div>
+    }
+}
+
+fn view_transitions(workflow: &Workflow, editable: bool) -> Html<Model> {
+    html! {
+        <>
+            <h3>{ "Allowed moves" }</h3>
+            <table class="table is-fullwidth",>
+                <thead>
+                    <tr>
+                        <th>{ "From \\ To" }</th>
+                        { for workflow.columns.iter().map(|column| html! { <th>{ &column.label }</th> }) }
+                    </tr>
+                </thead>
+                <tbody>
+                    { for workflow.columns.iter().enumerate().map(|(from, column)| view_transition_row(from, column, workflow, editable)) }
+                </tbody>
+            </table>
+        </>
+    }
+}
+
+fn view_transition_row(from: usize, column: &Column, workflow: &Workflow, editable: bool) -> Html<Model> {
+    html! {
+        <tr>
+            <th>{ &column.label }</th>
+            { for workflow.columns.iter().enumerate().map(|(to, target)| view_transition_cell(from, to, workflow.rule(&column.key, &target.key), editable)) }
+        </tr>
+    }
+}
+
+fn view_transition_cell(from: usize, to: usize, rule: Option<Rule>, editable: bool) -> Html<Model> {
+    if from == to {
+        return html! {
+            <td></td>
+        };
+    }
+
+    let value = match rule {
+        Some(Rule::Allowed) => "allowed",
+        Some(Rule::NeedsReason) => "reason",
+        None => "forbidden",
+    };
+
+    html! {
+        <td>
+            <div class="select is-small",>
+                <select value=value, disabled=!editable, onchange=|e| Msg::SetRule(from, to, e),>
+                    <option value="forbidden",>{ "—" }</option>
+                    <option value="allowed",>{ "allowed" }</option>
+                    <option value="reason",>{ "with reason" }</option>
+                </select>
+            </div>
+        </td>
+    }
+}
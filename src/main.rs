@@ -5,6 +5,7 @@ extern crate serde_derive;
 extern crate serde;
 #[macro_use]
 extern crate yew;
+#[macro_use]
 extern crate stdweb;
 
 use yew::prelude::App;
@@ -12,12 +13,18 @@ use yew::prelude::App;
 mod components;
 mod models;
 mod router;
+mod routes;
 mod routing;
+mod storage;
+mod store;
+mod url;
 
 use crate::components::app::Model;
 
 fn main() {
     yew::initialize();
+    routing::set_mode(routing::Mode::from_document());
+    routing::set_base_path(routing::base_path_from_document());
     App::<Model>::new().mount_to_body();
     yew::run_loop();
 }
@@ -1,3 +1,6 @@
+pub mod role;
 pub mod status;
 pub mod task;
+pub mod team;
 pub mod user;
+pub mod workflow;
@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// What a user may do, from least to most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Role {
+  /// Can look at the board but not change it.
+  Viewer,
+  /// Can create, edit, move and archive tasks.
+  Member,
+  /// Can also delete tasks, manage users and teams and change the workflow.
+  Admin,
+}
+
+pub const ROLES: [Role; 3] = [Role::Viewer, Role::Member, Role::Admin];
+
+/// The actions guarded by a role, see `Role::allows`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+  EditTasks,
+  DeleteTasks,
+  ManageUsers,
+  ManageWorkflow,
+}
+
+impl Role {
+  pub fn allows(self, permission: Permission) -> bool {
+    let needed = match permission {
+      Permission::EditTasks => Role::Member,
+      Permission::DeleteTasks | Permission::ManageUsers | Permission::ManageWorkflow => Role::Admin,
+    };
+    self >= needed
+  }
+
+  pub fn key(self) -> &'static str {
+    match self {
+      Role::Viewer => "viewer",
+      Role::Member => "member",
+      Role::Admin => "admin",
+    }
+  }
+
+  /// The inverse of `Role::key`.
+  pub fn from_key(key: &str) -> Option<Role> {
+    ROLES.iter().cloned().find(|role| role.key() == key)
+  }
+}
+
+impl Default for Role {
+  /// Users saved before roles existed could do everything, and keep doing so.
+  fn default() -> Self {
+    Role::Admin
+  }
+}
+
+impl fmt::Display for Role {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.key())
+  }
+}
@@ -1,27 +1,71 @@
+use serde::{Deserialize, Deserializer};
+
 use crate::models::status::Status;
+use crate::models::team::TeamId;
+use crate::models::user::UserId;
+
+/// Identifies a task for its whole lifetime, independent of where it sits on the board.
+pub type TaskId = u32;
 
+/// Missing fields fall back to `Task::default()`, so boards saved before a
+/// field was added keep loading.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Task {
+  /// `0` for drafts that have not been added to the board yet.
+  pub id: TaskId,
   pub name: String,
-  pub assignee: String,
+  #[serde(deserialize_with = "deserialize_assignee")]
+  pub assignee: Option<UserId>,
+  /// The team responsible for the task, which may not have picked an assignee yet.
+  pub team: Option<TeamId>,
   pub estimate: u32,
   pub status: Status,
+  /// Why the task last took a move that had to be justified, see `Rule::NeedsReason`.
+  pub reason: Option<String>,
+  /// Archived tasks are kept but no longer shown on the board.
+  pub archived: bool,
+  /// When the task reached the last column, in milliseconds since the epoch.
+  pub completed_at: Option<f64>,
+}
+
+impl Default for Task {
+  fn default() -> Self {
+    Task::create_empty()
+  }
 }
 
 impl Task {
   pub fn create_empty() -> Self {
     Task {
+      id: 0,
       name: "".to_string(),
-      assignee: "".to_string(),
+      assignee: None,
+      team: None,
       estimate: 0,
-      status: Status::ToDo,
+      status: Status::default(),
+      reason: None,
+      archived: false,
+      completed_at: None,
     }
   }
+}
 
-  pub fn can_left(&self) -> bool {
-    self.status != Status::ToDo
+/// Assignees used to be free-form strings (emoji) before they referenced users,
+/// those cannot be matched to anybody and load as unassigned.
+fn deserialize_assignee<'de, D>(deserializer: D) -> Result<Option<UserId>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Stored {
+    Id(UserId),
+    Legacy(String),
   }
 
-  pub fn can_right(&self) -> bool {
-    self.status != Status::Done
+  match Option::<Stored>::deserialize(deserializer)? {
+    Some(Stored::Id(id)) => Ok(Some(id)),
+    Some(Stored::Legacy(_)) | None => Ok(None),
   }
 }
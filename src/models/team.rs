@@ -0,0 +1,56 @@
+use crate::models::user::UserId;
+
+pub type TeamId = u32;
+
+/// A named group of users. Tasks can be handed to a team as a whole before
+/// one of its members picks them up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+  pub id: TeamId,
+  pub name: String,
+  /// Always one of `members`.
+  pub lead: Option<UserId>,
+  pub members: Vec<UserId>,
+}
+
+impl Team {
+  pub fn new(id: TeamId, name: String) -> Self {
+    Team {
+      id,
+      name,
+      lead: None,
+      members: vec![],
+    }
+  }
+
+  pub fn has_member(&self, id: UserId) -> bool {
+    self.members.contains(&id)
+  }
+
+  /// Adds or removes a member, a removed lead leaves the team without one.
+  pub fn toggle_member(&mut self, id: UserId) {
+    if self.has_member(id) {
+      self.members.retain(|member| *member != id);
+      if self.lead == Some(id) {
+        self.lead = None;
+      }
+    } else {
+      self.members.push(id);
+    }
+  }
+
+  /// Checks the name is filled in and no other team in `teams` uses it.
+  pub fn validate(&self, teams: &[Team]) -> Result<(), String> {
+    let name = self.name.trim().to_lowercase();
+    if name.is_empty() {
+      return Err("A team needs a name.".to_string());
+    }
+    if teams
+      .iter()
+      .any(|team| team.id != self.id && team.name.trim().to_lowercase() == name)
+    {
+      return Err(format!("There already is a team called {}.", self.name.trim()));
+    }
+    Ok(())
+  }
+}
@@ -1,35 +1,112 @@
 use std::fmt;
 
+use crate::models::role::Role;
+
+pub type UserId = u32;
+
+/// Hours a week, for users saved before capacities existed.
+const DEFAULT_CAPACITY: u32 = 40;
+
+fn default_capacity() -> u32 {
+  DEFAULT_CAPACITY
+}
+
+/// Background colors for generated initials avatars.
+const INITIALS_COLORS: [&'static str; 8] = [
+  "#e57373", "#ba68c8", "#7986cb", "#4fc3f7", "#4db6ac", "#aed581", "#ffb74d", "#a1887f",
+];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Avatar {
+  /// Shown as initials, like `Initials`, until the user picks something else.
   Empty,
   Emoji(String),
+  /// Generated from `User::full_name`, see `User::initials` and `User::color`.
+  Initials,
+  /// A `data:` URL of an uploaded picture.
+  Image(String),
 }
 
 impl fmt::Display for Avatar {
+  /// Only emoji have a textual form, the other avatars need `components::avatar`.
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
-      Avatar::Empty => write!(f, ""),
       Avatar::Emoji(emj) => write!(f, "{}", emj),
+      _ => write!(f, ""),
     }
   }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct User {
+  pub id: UserId,
   pub first_name: String,
   pub last_name: String,
   pub avatar: Avatar,
+  /// Hours of estimated work the user can take on per week.
+  #[serde(default = "default_capacity")]
+  pub capacity: u32,
+  #[serde(default)]
+  pub role: Role,
 }
 
 impl User {
-  pub fn new(fname: String, lname: String, avatar: Avatar) -> Self {
+  pub fn new(id: UserId, fname: String, lname: String, avatar: Avatar) -> Self {
     User {
+      id,
       first_name: fname,
       last_name: lname,
       avatar: avatar,
+      capacity: DEFAULT_CAPACITY,
+      role: Role::Member,
     }
   }
 
   pub fn full_name(&self) -> String {
     format!("{} {}", self.first_name, self.last_name)
   }
+
+  /// Up to two letters, the first ones of the first and last name.
+  pub fn initials(&self) -> String {
+    self
+      .first_name
+      .trim()
+      .chars()
+      .take(1)
+      .chain(self.last_name.trim().chars().take(1))
+      .flat_map(char::to_uppercase)
+      .collect()
+  }
+
+  /// A color derived from the full name, so a user keeps the same one everywhere.
+  pub fn color(&self) -> &'static str {
+    let hash = self
+      .full_name()
+      .chars()
+      .fold(0u32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as u32));
+    INITIALS_COLORS[hash as usize % INITIALS_COLORS.len()]
+  }
+
+  /// Checks the names are filled in and nobody else in `users` has the same full name.
+  pub fn validate(&self, users: &[User]) -> Result<(), String> {
+    if self.first_name.trim().is_empty() || self.last_name.trim().is_empty() {
+      return Err("First and last name are required.".to_string());
+    }
+    let full_name = self.full_name().trim().to_lowercase();
+    let duplicate = users
+      .iter()
+      .any(|user| user.id != self.id && user.full_name().trim().to_lowercase() == full_name);
+    if duplicate {
+      return Err(format!("There already is a user called {}.", self.full_name()));
+    }
+    Ok(())
+  }
+
+  /// The avatar followed by the full name, for places that can only show text.
+  pub fn label(&self) -> String {
+    match self.avatar {
+      Avatar::Emoji(_) => format!("{} {}", self.avatar, self.full_name()),
+      _ => self.full_name(),
+    }
+  }
 }
@@ -0,0 +1,246 @@
+use crate::models::status::Status;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+  pub key: Status,
+  pub label: String,
+  pub color: String,
+  /// Work-in-progress limit, the number of tasks the column should hold at most.
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+impl Column {
+  pub fn new(key: &str, label: &str, color: &str) -> Self {
+    Column {
+      key: Status::from(key),
+      label: label.to_string(),
+      color: color.to_string(),
+      limit: None,
+    }
+  }
+
+  pub fn is_over_limit(&self, count: usize) -> bool {
+    self.limit.map_or(false, |limit| count > limit)
+  }
+
+  /// Whether one more task would break the limit.
+  pub fn is_full(&self, count: usize) -> bool {
+    self.limit.map_or(false, |limit| count >= limit)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Rule {
+  Allowed,
+  /// The move has to be justified, e.g. reopening a finished task.
+  NeedsReason,
+}
+
+/// An edge of the transition graph: tasks in `from` may be moved to `to`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+  pub from: Status,
+  pub to: Status,
+  pub rule: Rule,
+}
+
+impl Transition {
+  pub fn new(from: &str, to: &str, rule: Rule) -> Self {
+    Transition {
+      from: Status::from(from),
+      to: Status::from(to),
+      rule,
+    }
+  }
+}
+
+/// The ordered list of columns a board is made of, and the moves allowed between them.
+/// Tasks travel from the first column to the last one, which means "done".
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+  pub columns: Vec<Column>,
+  /// `None` lets every column move to its neighbours, which is how workflows
+  /// saved before transitions existed keep working. `Some` lists every allowed
+  /// move, an empty list forbids them all.
+  #[serde(default)]
+  pub transitions: Option<Vec<Transition>>,
+  /// Ask before moving a task into a column that reached its limit.
+  #[serde(default)]
+  pub block_full_columns: bool,
+}
+
+impl Default for Workflow {
+  fn default() -> Self {
+    Workflow {
+      columns: vec![
+        Column::new("todo", "To be done", "#d32f2f"),
+        Column::new("in_progress", "In progress", "#dc4b22"),
+        Column::new("review", "Review", "#fbc02d"),
+        Column::new("testing", "Testing", "#cddc39"),
+        Column::new("ready", "Ready", "#8bc34a"),
+        Column::new("done", "Done", "#4caf50"),
+      ],
+      transitions: Some(vec![
+        Transition::new("todo", "in_progress", Rule::Allowed),
+        Transition::new("in_progress", "todo", Rule::Allowed),
+        Transition::new("in_progress", "review", Rule::Allowed),
+        Transition::new("review", "in_progress", Rule::Allowed),
+        Transition::new("review", "testing", Rule::Allowed),
+        Transition::new("testing", "review", Rule::Allowed),
+        Transition::new("testing", "todo", Rule::Allowed),
+        Transition::new("testing", "ready", Rule::Allowed),
+        Transition::new("ready", "testing", Rule::Allowed),
+        Transition::new("ready", "done", Rule::Allowed),
+        Transition::new("done", "ready", Rule::NeedsReason),
+      ]),
+      block_full_columns: false,
+    }
+  }
+}
+
+impl Workflow {
+  pub fn position(&self, status: &Status) -> Option<usize> {
+    self.columns.iter().position(|column| &column.key == status)
+  }
+
+  pub fn column(&self, status: &Status) -> Option<&Column> {
+    self.columns.iter().find(|column| &column.key == status)
+  }
+
+  pub fn contains(&self, status: &Status) -> bool {
+    self.position(status).is_some()
+  }
+
+  /// The column new tasks start in.
+  pub fn first(&self) -> Option<&Status> {
+    self.columns.first().map(|column| &column.key)
+  }
+
+  pub fn is_done(&self, status: &Status) -> bool {
+    self.columns.last().map(|column| &column.key) == Some(status)
+  }
+
+  pub fn left(&self, status: &Status) -> Option<Status> {
+    match self.position(status) {
+      Some(idx) if idx > 0 => Some(self.columns[idx - 1].key.clone()),
+      _ => None,
+    }
+  }
+
+  pub fn right(&self, status: &Status) -> Option<Status> {
+    self
+      .position(status)
+      .and_then(|idx| self.columns.get(idx + 1))
+      .map(|column| column.key.clone())
+  }
+
+  /// How a task in `from` may be moved to `to`, `None` if it may not.
+  /// Reordering inside a column is always allowed.
+  pub fn rule(&self, from: &Status, to: &Status) -> Option<Rule> {
+    if from == to {
+      return Some(Rule::Allowed);
+    }
+    match self.transitions {
+      None => {
+        let neighbours = self.left(from).as_ref() == Some(to) || self.right(from).as_ref() == Some(to);
+        if neighbours { Some(Rule::Allowed) } else { None }
+      }
+      Some(ref transitions) => transitions
+        .iter()
+        .find(|transition| &transition.from == from && &transition.to == to)
+        .map(|transition| transition.rule),
+    }
+  }
+
+  pub fn set_rule(&mut self, from: &Status, to: &Status, rule: Option<Rule>) {
+    if from == to {
+      return;
+    }
+    if self.transitions.is_none() {
+      self.transitions = Some(self.neighbour_transitions());
+    }
+    if let Some(ref mut transitions) = self.transitions {
+      transitions.retain(|transition| !(&transition.from == from && &transition.to == to));
+      if let Some(rule) = rule {
+        transitions.push(Transition {
+          from: from.clone(),
+          to: to.clone(),
+          rule,
+        });
+      }
+    }
+  }
+
+  /// The explicit form of the implicit rules used while `transitions` is `None`.
+  fn neighbour_transitions(&self) -> Vec<Transition> {
+    self
+      .columns
+      .windows(2)
+      .flat_map(|pair| {
+        vec![
+          Transition {
+            from: pair[0].key.clone(),
+            to: pair[1].key.clone(),
+            rule: Rule::Allowed,
+          },
+          Transition {
+            from: pair[1].key.clone(),
+            to: pair[0].key.clone(),
+            rule: Rule::Allowed,
+          },
+        ]
+      })
+      .collect()
+  }
+
+  /// Appends a column, deriving a key from the label that no other column uses.
+  /// The new column is connected both ways to the one that used to be last.
+  pub fn add_column(&mut self, label: &str, color: &str) {
+    let base: String = label
+      .trim()
+      .to_lowercase()
+      .chars()
+      .map(|c| if c.is_alphanumeric() { c } else { '_' })
+      .collect();
+    let base = if base.is_empty() { "column".to_string() } else { base };
+
+    let mut key = base.clone();
+    let mut suffix = 2;
+    while self.contains(&Status::from(key.as_str())) {
+      key = format!("{}_{}", base, suffix);
+      suffix += 1;
+    }
+
+    let previous = self.columns.last().map(|column| column.key.clone());
+    let key = Status::from(key.as_str());
+    self.columns.push(Column {
+      key: key.clone(),
+      label: label.trim().to_string(),
+      color: color.to_string(),
+      limit: None,
+    });
+
+    if let (Some(previous), true) = (previous, self.transitions.is_some()) {
+      self.set_rule(&previous, &key, Some(Rule::Allowed));
+      self.set_rule(&key, &previous, Some(Rule::Allowed));
+    }
+  }
+
+  pub fn move_column(&mut self, from: usize, to: usize) {
+    if from < self.columns.len() && to < self.columns.len() {
+      let column = self.columns.remove(from);
+      self.columns.insert(to, column);
+    }
+  }
+
+  /// Removes a column unless it is the last one left, a board needs somewhere to put tasks.
+  pub fn remove_column(&mut self, idx: usize) {
+    if self.columns.len() > 1 && idx < self.columns.len() {
+      let key = self.columns.remove(idx).key;
+      if let Some(ref mut transitions) = self.transitions {
+        transitions.retain(|transition| transition.from != key && transition.to != key);
+      }
+    }
+  }
+}
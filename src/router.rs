@@ -2,7 +2,7 @@
 
 use yew::prelude::worker::*;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use stdweb::unstable::TryFrom;
 use stdweb::JsSerialize;
@@ -13,12 +13,17 @@ use serde::Serialize;
 
 use std::fmt::Debug;
 
+use yew::services::DialogService;
+
 use crate::routing::RouteService;
+use crate::url::{percent_decode, percent_encode, Query};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Route<T> {
+    /// Decoded, so a segment may contain a '/' of its own.
     pub path_segments: Vec<String>,
-    pub query: Option<String>,
+    pub query: Query,
+    /// Decoded, without the leading '#'.
     pub fragment: Option<String>,
     pub state: T,
 }
@@ -27,35 +32,32 @@ impl<T> Route<T>
 where
     T: JsSerialize + Clone + TryFrom<Value> + Default + 'static,
 {
+    /// The route as a percent-encoded URL path, query and fragment.
     pub fn to_route_string(&self) -> String {
-        let path = self.path_segments.join("/");
-        let mut path = format!("/{}", path); // add the leading '/'
-        if let Some(ref query) = self.query {
-            path = format!("{}?{}", path, query);
+        let segments: Vec<String> = self.path_segments.iter().map(|segment| percent_encode(segment)).collect();
+        let mut path = format!("/{}", segments.join("/")); // add the leading '/'
+        if !self.query.is_empty() {
+            path = format!("{}?{}", path, self.query);
         }
         if let Some(ref fragment) = self.fragment {
-            path = format!("{}#{}", path, fragment)
+            path = format!("{}#{}", path, percent_encode(fragment))
         }
         path
     }
 
+    /// The route the browser shows, with the state of its history entry.
     pub fn current_route(route_service: &RouteService<T>) -> Self {
         let path = route_service.get_path(); // guaranteed to always start with a '/'
-        let mut path_segments: Vec<String> = path.split("/").map(String::from).collect();
+        let mut path_segments: Vec<String> = path.split("/").map(percent_decode).collect();
         path_segments.remove(0); // remove empty string that is split from the first '/'
 
-        let mut query: String = route_service.get_query(); // The first character will be a '?'
-        let query: Option<String> = if query.len() > 1 {
-            query.remove(0);
-            Some(query)
-        } else {
-            None
-        };
+        let query: String = route_service.get_query(); // The first character will be a '?'
+        let query = Query::parse(query.trim_start_matches('?'));
 
         let mut fragment: String = route_service.get_fragment(); // The first character will be a '#'
         let fragment: Option<String> = if fragment.len() > 1 {
             fragment.remove(0);
-            Some(fragment)
+            Some(percent_decode(&fragment))
         } else {
             None
         };
@@ -64,7 +66,7 @@ where
             path_segments,
             query,
             fragment,
-            state: T::default(),
+            state: route_service.get_state().unwrap_or_default(),
         }
     }
 }
@@ -84,7 +86,66 @@ pub enum Request<T> {
     ChangeRoute(Route<T>),
     /// Changes the route using a RouteInfo struct, but does not alert connected components to the route change.
     ChangeRouteNoBroadcast(Route<T>),
+    /// Like `ChangeRoute`, but replaces the current history entry instead of adding one,
+    /// for routes that change with every keystroke.
+    ReplaceRoute(Route<T>),
+    /// Like `ChangeRouteNoBroadcast`, but replaces the current history entry instead of adding one.
+    ReplaceRouteNoBroadcast(Route<T>),
+    /// Goes back one history entry, the route found there is broadcast.
+    Back,
+    /// Goes forward one history entry, the route found there is broadcast.
+    Forward,
+    /// Moves through the history by the given offset, the route found there is broadcast.
+    Go(i32),
     GetCurrentRoute,
+    /// Guards route changes made with `ChangeRoute`, `Back`, `Forward`, `Go` and the browser's
+    /// buttons, as well as closing or reloading the page, until it is set to `None` or the
+    /// component disconnects. Replacing routes stays unguarded.
+    SetGuard(Option<Guard>),
+}
+
+/// What a component with unsaved changes wants to happen when the route is about to change.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum Guard {
+    /// Asks the user to confirm leaving, with the given message.
+    Confirm(String),
+    /// Refuses to leave, telling the user why.
+    Block(String),
+}
+
+impl Guard {
+    fn message(&self) -> &str {
+        match self {
+            Guard::Confirm(message) | Guard::Block(message) => message,
+        }
+    }
+}
+
+/// The `Guard::Confirm` a component keeps registered with the router while it has unsaved changes.
+#[derive(Default)]
+pub struct ConfirmGuard {
+    message: Option<String>,
+}
+
+impl ConfirmGuard {
+    /// Registers `message` as the question to ask before leaving, `None` lifts the guard.
+    /// The router only hears about it when it changes.
+    pub fn update<T>(&mut self, router: &mut Bridge<Router<T>>, message: Option<String>)
+    where
+        for<'de> T: JsSerialize
+            + Clone
+            + Debug
+            + TryFrom<Value>
+            + Default
+            + Serialize
+            + Deserialize<'de>
+            + 'static,
+    {
+        if message != self.message {
+            router.send(Request::SetGuard(message.clone().map(Guard::Confirm)));
+            self.message = message;
+        }
+    }
 }
 
 impl<T> Transferable for Request<T> where for<'de> T: Serialize + Deserialize<'de> {}
@@ -107,6 +168,89 @@ where
     /// When a route changes, either initiated by the browser or by the app,
     /// the route change will be broadcast to all listening entities.
     subscribers: HashSet<HandlerId>,
+    guards: HashMap<HandlerId, Guard>,
+    dialog: DialogService,
+    /// The history index of the route last broadcast or set, moved back to when a guard
+    /// vetoes the browser's back or forward button.
+    index: i32,
+    /// The history index a `Back`, `Forward` or `Go` the guards already let pass heads to,
+    /// so the `popstate` arriving there does not ask again.
+    confirmed: Option<i32>,
+}
+
+impl<T> Router<T>
+where
+    for<'de> T: JsSerialize
+        + Clone
+        + Debug
+        + TryFrom<Value>
+        + Default
+        + Serialize
+        + Deserialize<'de>
+        + 'static,
+{
+    /// The route the browser now shows, with the exact state that was set for it.
+    fn route_with_state(&self, state: T) -> Route<T> {
+        Route {
+            state,
+            ..Route::current_route(&self.route_service)
+        }
+    }
+
+    fn broadcast(&mut self, route: Route<T>) {
+        for sub in self.subscribers.iter() {
+            self.link.response(*sub, route.clone());
+        }
+        self.track_index();
+    }
+
+    /// Remembers where in the history the browser now is.
+    fn track_index(&mut self) {
+        self.index = self.route_service.index();
+        self.confirmed = None;
+    }
+
+    /// Asks the guards whether the current route may be left.
+    fn may_leave(&mut self) -> bool {
+        if let Some(guard) = self.guards.values().find(|guard| match guard {
+            Guard::Block(_) => true,
+            Guard::Confirm(_) => false,
+        }) {
+            let message = guard.message().to_string();
+            self.dialog.alert(&message);
+            return false;
+        }
+
+        let questions: Vec<&str> = self.guards.values().map(Guard::message).collect();
+        if questions.is_empty() {
+            return true;
+        }
+        let question = questions.join("\n");
+        self.dialog.confirm(&question)
+    }
+
+    /// Moves `offset` entries through the history if the guards let it.
+    fn move_in_history(&mut self, offset: i32) {
+        if !self.may_leave() {
+            return;
+        }
+        // Should there be nothing to move to, no `popstate` arrives at this index and it is dropped with the next route.
+        self.confirmed = match self.guards.is_empty() {
+            true => None,
+            false => Some(self.index + offset),
+        };
+        self.route_service.go(offset);
+    }
+
+    /// Keeps the browser from closing or reloading the page silently while any guard is set.
+    fn update_unload_warning(&mut self) {
+        let messages: Vec<&str> = self.guards.values().map(Guard::message).collect();
+        let message = match messages.is_empty() {
+            true => None,
+            false => Some(messages.join("\n")),
+        };
+        self.route_service.set_unload_warning(message);
+    }
 }
 
 impl<T> Agent for Router<T>
@@ -131,11 +275,16 @@ where
         });
         let mut route_service = RouteService::new();
         route_service.register_callback(callback);
+        let index = route_service.index();
 
         Router {
             link,
             route_service,
             subscribers: HashSet::new(),
+            guards: HashMap::new(),
+            dialog: DialogService::new(),
+            index,
+            confirmed: None,
         }
     }
 
@@ -143,11 +292,19 @@ where
         match msg {
             Msg::BrowserNavigationRouteChanged((_route_string, state)) => {
                 info!("Browser navigated");
-                let mut route = Route::current_route(&self.route_service);
-                route.state = state;
-                for sub in self.subscribers.iter() {
-                    self.link.response(*sub, route.clone());
+                let index = self.route_service.index();
+                if index == self.index {
+                    // Back where the last route was set after a vetoed move was undone.
+                    return;
+                }
+                let confirmed = self.confirmed.take() == Some(index);
+                if !confirmed && !self.may_leave() {
+                    // The browser already moved on, undo the move so the history stays as it was.
+                    self.route_service.go(self.index - index);
+                    return;
                 }
+                let route = self.route_with_state(state);
+                self.broadcast(route);
             }
         }
     }
@@ -156,24 +313,48 @@ where
         info!("Request: {:?}", msg);
         match msg {
             Request::ChangeRoute(route) => {
+                if !self.may_leave() {
+                    return;
+                }
                 let route_string: String = route.to_route_string();
                 // set the route
-                self.route_service.set_route(&route_string, route.state);
-                // get the new route. This will contain a default state object
-                let route = Route::current_route(&self.route_service);
+                self.route_service.set_route(&route_string, route.state.clone());
+                // get the new route as the browser sees it, but with the exact state that was pushed
+                let route = self.route_with_state(route.state);
                 // broadcast it to all listening components
-                for sub in self.subscribers.iter() {
-                    self.link.response(*sub, route.clone());
-                }
+                self.broadcast(route);
             }
             Request::ChangeRouteNoBroadcast(route) => {
                 let route_string: String = route.to_route_string();
                 self.route_service.set_route(&route_string, route.state);
+                self.track_index();
+            }
+            Request::ReplaceRoute(route) => {
+                let route_string: String = route.to_route_string();
+                self.route_service.replace_route(&route_string, route.state.clone());
+                let route = self.route_with_state(route.state);
+                self.broadcast(route);
             }
+            Request::ReplaceRouteNoBroadcast(route) => {
+                let route_string: String = route.to_route_string();
+                self.route_service.replace_route(&route_string, route.state);
+                self.track_index();
+            }
+            // The browser reports where these end up with a `popstate`, which is broadcast in `update`.
+            Request::Back => self.move_in_history(-1),
+            Request::Forward => self.move_in_history(1),
+            Request::Go(offset) => self.move_in_history(offset),
             Request::GetCurrentRoute => {
                 let route = Route::current_route(&self.route_service);
                 self.link.response(who, route.clone());
             }
+            Request::SetGuard(guard) => {
+                match guard {
+                    Some(guard) => self.guards.insert(who, guard),
+                    None => self.guards.remove(&who),
+                };
+                self.update_unload_warning();
+            }
         }
     }
 
@@ -182,5 +363,8 @@ where
     }
     fn disconnected(&mut self, id: HandlerId) {
         self.subscribers.remove(&id);
+        if self.guards.remove(&id).is_some() {
+            self.update_unload_warning();
+        }
     }
 }
@@ -0,0 +1,223 @@
+//! The routes of the app, declared once as patterns and translated to and from `router::Route`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::task::TaskId;
+use crate::models::user::UserId;
+use crate::router::Route;
+use crate::url::Query;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AppRoute {
+    Board,
+    /// The board with the detail panel of a task open.
+    Task(TaskId),
+    Archive,
+    Users,
+    User(UserId),
+    Teams,
+    Workflow,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RouteError {
+    /// No pattern matches the path.
+    NotFound(String),
+    /// A pattern matches, but one of its parameters cannot be parsed.
+    BadParameter { name: &'static str, value: String },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::NotFound(path) => write!(f, "Not found: '{}'", path),
+            RouteError::BadParameter { name, value } => write!(f, "Invalid {}: '{}'", name, value),
+        }
+    }
+}
+
+/// The parameters a pattern extracted from a path, by name.
+struct Params(Vec<(&'static str, String)>);
+
+impl Params {
+    fn parse<V: FromStr>(&self, name: &'static str) -> Result<V, RouteError> {
+        let value = self
+            .0
+            .iter()
+            .find(|(param, _)| *param == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or("");
+        value.parse().map_err(|_| RouteError::BadParameter {
+            name,
+            value: value.to_string(),
+        })
+    }
+}
+
+/// A piece of a route's path.
+enum Segment {
+    /// Has to match literally.
+    Literal(&'static str),
+    /// Matches any segment, which is handed over under this name.
+    Param(&'static str),
+}
+
+const TASK_ID: &'static str = "task_id";
+const USER_ID: &'static str = "user_id";
+
+const BOARD: &'static [Segment] = &[Segment::Literal("board")];
+const TASK: &'static [Segment] = &[Segment::Literal("board"), Segment::Literal("task"), Segment::Param(TASK_ID)];
+const ARCHIVE: &'static [Segment] = &[Segment::Literal("archive")];
+const USERS: &'static [Segment] = &[Segment::Literal("users")];
+const USER: &'static [Segment] = &[Segment::Literal("users"), Segment::Param(USER_ID)];
+const TEAMS: &'static [Segment] = &[Segment::Literal("teams")];
+const WORKFLOW: &'static [Segment] = &[Segment::Literal("workflow")];
+
+/// How to read each pattern back into a route, tried in order. `AppRoute::pattern` goes the other way.
+const PARSERS: &'static [(&'static [Segment], fn(&Params) -> Result<AppRoute, RouteError>)] = &[
+    (BOARD, |_| Ok(AppRoute::Board)),
+    (TASK, |params| Ok(AppRoute::Task(params.parse(TASK_ID)?))),
+    (ARCHIVE, |_| Ok(AppRoute::Archive)),
+    (USERS, |_| Ok(AppRoute::Users)),
+    (USER, |params| Ok(AppRoute::User(params.parse(USER_ID)?))),
+    (TEAMS, |_| Ok(AppRoute::Teams)),
+    (WORKFLOW, |_| Ok(AppRoute::Workflow)),
+];
+
+/// Matches path segments against a pattern, a trailing slash makes no difference.
+fn match_pattern(pattern: &[Segment], path_segments: &[String]) -> Option<Params> {
+    let segments: Vec<&String> = path_segments.iter().filter(|segment| !segment.is_empty()).collect();
+    if segments.len() != pattern.len() {
+        return None;
+    }
+
+    let mut params = vec![];
+    for (expected, segment) in pattern.iter().zip(segments) {
+        match *expected {
+            Segment::Literal(literal) if literal != segment.as_str() => return None,
+            Segment::Literal(_) => {}
+            Segment::Param(name) => params.push((name, segment.clone())),
+        }
+    }
+    Some(Params(params))
+}
+
+/// Fills the parameters of a pattern in by name.
+///
+/// # Panics
+/// When a parameter has no value, that is a mistake in `AppRoute::pattern`.
+fn fill_pattern(pattern: &[Segment], values: &[(&'static str, String)]) -> Vec<String> {
+    pattern
+        .iter()
+        .map(|segment| match *segment {
+            Segment::Literal(literal) => literal.to_string(),
+            Segment::Param(name) => values
+                .iter()
+                .find(|(param, _)| *param == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_else(|| panic!("no value for the route parameter '{}'", name)),
+        })
+        .collect()
+}
+
+impl AppRoute {
+    /// The pattern of the route, with the values of its parameters by name.
+    fn pattern(&self) -> (&'static [Segment], Vec<(&'static str, String)>) {
+        match self {
+            AppRoute::Board => (BOARD, vec![]),
+            AppRoute::Task(id) => (TASK, vec![(TASK_ID, id.to_string())]),
+            AppRoute::Archive => (ARCHIVE, vec![]),
+            AppRoute::Users => (USERS, vec![]),
+            AppRoute::User(id) => (USER, vec![(USER_ID, id.to_string())]),
+            AppRoute::Teams => (TEAMS, vec![]),
+            AppRoute::Workflow => (WORKFLOW, vec![]),
+        }
+    }
+
+    pub fn path_segments(&self) -> Vec<String> {
+        let (pattern, values) = self.pattern();
+        fill_pattern(pattern, &values)
+    }
+
+    pub fn to_route(&self) -> Route<()> {
+        Route {
+            path_segments: self.path_segments(),
+            query: Query::default(),
+            fragment: None,
+            state: (),
+        }
+    }
+
+    pub fn from_route<T>(route: &Route<T>) -> Result<Self, RouteError> {
+        for (pattern, parse) in PARSERS.iter() {
+            if let Some(params) = match_pattern(pattern, &route.path_segments) {
+                return parse(&params);
+            }
+        }
+        Err(RouteError::NotFound(route.path_segments.join("/")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(path: &str) -> Vec<String> {
+        path.split('/').skip(1).map(String::from).collect()
+    }
+
+    fn route(path: &str) -> Route<()> {
+        Route {
+            path_segments: segments(path),
+            query: Query::default(),
+            fragment: None,
+            state: (),
+        }
+    }
+
+    #[test]
+    fn match_pattern_extracts_params() {
+        let params = match_pattern(TASK, &segments("/board/task/7")).unwrap();
+        assert_eq!(params.0, vec![(TASK_ID, "7".to_string())]);
+        assert!(match_pattern(TASK, &segments("/board/7")).is_none());
+        assert!(match_pattern(BOARD, &segments("/archive")).is_none());
+    }
+
+    #[test]
+    fn match_pattern_ignores_trailing_slash() {
+        assert!(match_pattern(BOARD, &segments("/board/")).is_some());
+        assert!(match_pattern(USER, &segments("/users/3/")).is_some());
+    }
+
+    #[test]
+    fn routes_round_trip() {
+        let routes = vec![
+            AppRoute::Board,
+            AppRoute::Task(7),
+            AppRoute::Archive,
+            AppRoute::Users,
+            AppRoute::User(3),
+            AppRoute::Teams,
+            AppRoute::Workflow,
+        ];
+        for app_route in routes {
+            assert_eq!(AppRoute::from_route(&app_route.to_route()), Ok(app_route));
+        }
+    }
+
+    #[test]
+    fn bad_params_and_unknown_paths_are_errors() {
+        assert_eq!(
+            AppRoute::from_route(&route("/users/jane")),
+            Err(RouteError::BadParameter {
+                name: USER_ID,
+                value: "jane".to_string(),
+            })
+        );
+        assert_eq!(
+            AppRoute::from_route(&route("/nowhere/else")),
+            Err(RouteError::NotFound("nowhere/else".to_string()))
+        );
+    }
+}
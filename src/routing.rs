@@ -1,17 +1,99 @@
 //! Service to handle routing.
 
-use stdweb::web::History;
 use stdweb::web::Location;
-use stdweb::web::window;
+use stdweb::web::{document, window};
 use stdweb::Value;
 use stdweb::web::EventListenerHandle;
-use stdweb::web::event::PopStateEvent;
-use stdweb::web::IEventTarget;
+use stdweb::web::event::{HashChangeEvent, PopStateEvent};
+use stdweb::web::{IElement, IEventTarget, IParentNode};
 use stdweb::JsSerialize;
 use stdweb::unstable::TryFrom;
 use yew::callback::Callback;
 
+use std::cell::{Cell, RefCell};
 use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Where the route lives in the URL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// `/board/...`, needs the server to answer every path with `index.html`.
+    Path,
+    /// `/#/board/...`, works on any static file server.
+    Hash,
+}
+
+impl Mode {
+    /// Reads the mode from `<meta name="routing" content="hash">`, defaulting to `Mode::Path`.
+    pub fn from_document() -> Mode {
+        let content = document()
+            .query_selector("meta[name=routing]")
+            .ok()
+            .and_then(|meta| meta)
+            .and_then(|meta| meta.get_attribute("content"));
+        match content.as_ref().map(String::as_str) {
+            Some("hash") => Mode::Hash,
+            _ => Mode::Path,
+        }
+    }
+}
+
+/// The state stored with the current history entry, `null` if there is none.
+/// Entries are stored as `{ index, state }`, see `history_index`.
+fn history_state() -> Value {
+    js! {
+        var entry = history.state;
+        return entry ? entry.state : null;
+    }
+}
+
+/// The position of the current history entry, so moves through the history can be measured and undone.
+/// Entries the service did not create itself, like the one the page was loaded with, are given one first;
+/// those are always the newest entry, so their position follows from the length of the history.
+fn history_index() -> i32 {
+    let index = js! {
+        var entry = history.state;
+        if (!entry || typeof entry.index !== "number") {
+            entry = { index: history.length - 1, state: entry };
+            history.replaceState(entry, "");
+        }
+        return entry.index;
+    };
+    i32::try_from(index).unwrap_or(0)
+}
+
+/// Reads the path the app is deployed under from `<base href="/tools/kanban/">`, relative or absolute,
+/// as the directory the browser resolves relative URLs against. It comes with a leading and no trailing '/',
+/// or is empty when the app owns the whole origin or the page has no `<base>`.
+pub fn base_path_from_document() -> String {
+    let has_base = document().query_selector("base").ok().and_then(|base| base).is_some();
+    if !has_base {
+        // Without one `baseURI` is the page itself, which is any of the app's routes.
+        return String::new();
+    }
+    let pathname = js! { return new URL(document.baseURI).pathname; };
+    let pathname = String::try_from(pathname).unwrap_or_default();
+    match pathname.rfind('/') {
+        Some(idx) => pathname[..idx].to_string(),
+        None => String::new(),
+    }
+}
+
+thread_local! {
+    static MODE: Cell<Mode> = Cell::new(Mode::Path);
+    static BASE_PATH: RefCell<String> = RefCell::new(String::new());
+}
+
+/// Picks the mode of every route service created afterwards, call it at startup.
+pub fn set_mode(mode: Mode) {
+    MODE.with(|current| current.set(mode));
+}
+
+/// Sets the path every route service created afterwards lives under, call it at startup.
+/// Only `Mode::Path` needs it, in `Mode::Hash` the routes live behind the '#' of whatever page serves the app.
+pub fn set_base_path(base_path: String) {
+    BASE_PATH.with(|current| *current.borrow_mut() = base_path);
+}
 
 
 /// A service that facilitates manipulation of the browser's URL bar and responding to browser
@@ -19,9 +101,14 @@ use std::marker::PhantomData;
 ///
 /// The `T` determines what route state can be stored in the route service.
 pub struct RouteService<T> {
-    history: History,
     location: Location,
+    mode: Mode,
+    /// Prepended to routes set and stripped from the path read in `Mode::Path`, see `set_base_path`.
+    base_path: String,
     event_listener: Option<EventListenerHandle>,
+    hash_listener: Option<EventListenerHandle>,
+    /// The route last reported or set, so a `hashchange` following a `popstate` is not reported twice.
+    last_route: Rc<RefCell<String>>,
     phantom_data: PhantomData<T>
 }
 
@@ -32,10 +119,14 @@ impl <T> RouteService<T>
     /// Creates the route service.
     pub fn new() -> RouteService<T> {
         let location = window().location().expect("browser does not support location API");
+        history_index();
         RouteService {
-            history: window().history(),
             location,
+            mode: MODE.with(Cell::get),
+            base_path: BASE_PATH.with(|base_path| base_path.borrow().clone()),
             event_listener: None,
+            hash_listener: None,
+            last_route: Rc::new(RefCell::new(String::new())),
             phantom_data: PhantomData
         }
     }
@@ -43,21 +134,47 @@ impl <T> RouteService<T>
     /// Registers a callback to the route service.
     /// Callbacks will be called when the History API experiences a change such as
     /// popping a state off of its stack when the forward or back buttons are pressed.
+    /// In `Mode::Hash` changes of the hash are reported as well.
     pub fn register_callback(&mut self, callback: Callback<(String, T)>) {
+        let mode = self.mode;
+        let base_path = self.base_path.clone();
+        let last_route = self.last_route.clone();
+        let popstate_callback = callback.clone();
         self.event_listener = Some(window().add_event_listener(
-            move |event: PopStateEvent| {
-                let state_value: Value = event.state();
+            move |_: PopStateEvent| {
+                // Typing a new fragment also lands here, on an entry that has no index yet.
+                history_index();
 
-                if let Ok(state) = T::try_from(state_value) {
+                if let Ok(state) = T::try_from(history_state()) {
                     let location: Location = window().location().unwrap();
-                    let route: String = Self::get_route_from_location(&location);
+                    let route: String = Self::get_route_from_location(&location, mode, &base_path);
+                    *last_route.borrow_mut() = route.clone();
 
-                    callback.emit((route.clone(), state.clone()))
+                    popstate_callback.emit((route.clone(), state.clone()))
                 } else {
                     eprintln!("Nothing farther back in history, not calling routing callback.");
                 }
             },
         ));
+
+        if mode == Mode::Hash {
+            let base_path = self.base_path.clone();
+            let last_route = self.last_route.clone();
+            self.hash_listener = Some(window().add_event_listener(
+                move |_: HashChangeEvent| {
+                    let location: Location = window().location().unwrap();
+                    let route: String = Self::get_route_from_location(&location, mode, &base_path);
+                    if *last_route.borrow() == route {
+                        return;
+                    }
+                    history_index();
+                    if let Ok(state) = T::try_from(history_state()) {
+                        *last_route.borrow_mut() = route.clone();
+                        callback.emit((route, state))
+                    }
+                },
+            ));
+        }
     }
 
 
@@ -66,41 +183,131 @@ impl <T> RouteService<T>
     /// The route should be a relative path that starts with a '/'.
     /// A state object be stored with the url.
     pub fn set_route(&mut self, route: &str, state: T) {
+        *self.last_route.borrow_mut() = route.to_string();
+        let url = self.to_url(route);
+        let index = history_index() + 1;
 
-        self.history.push_state(
-            state,
-            "",
-            Some(route),
-        );
+        js! { @(no_return)
+            history.pushState({ index: @{index}, state: @{state} }, "", @{url});
+        }
+    }
+
+    /// Like `set_route`, but replaces the current history entry instead of adding one.
+    pub fn replace_route(&mut self, route: &str, state: T) {
+        *self.last_route.borrow_mut() = route.to_string();
+        let url = self.to_url(route);
+        let index = history_index();
+
+        js! { @(no_return)
+            history.replaceState({ index: @{index}, state: @{state} }, "", @{url});
+        }
+    }
+
+    /// Moves `offset` entries through the history, like the browser's back and forward buttons.
+    /// The resulting route is reported to the registered callback.
+    pub fn go(&mut self, offset: i32) {
+        js! { @(no_return)
+            history.go(@{offset});
+        }
+    }
+
+    /// The position of the current history entry, counted from the first one.
+    pub fn index(&self) -> i32 {
+        history_index()
+    }
+
+    /// Makes the browser ask before closing or reloading the page, `None` lets it go silently again.
+    /// Most browsers show a generic text instead of the message.
+    pub fn set_unload_warning(&mut self, message: Option<String>) {
+        match message {
+            Some(message) => js! { @(no_return)
+                var message = @{message};
+                window.onbeforeunload = function(event) {
+                    event.preventDefault();
+                    event.returnValue = message;
+                    return message;
+                };
+            },
+            None => js! { @(no_return)
+                window.onbeforeunload = null;
+            },
+        }
+    }
+
+    fn to_url(&self, route: &str) -> String {
+        match self.mode {
+            Mode::Path => format!("{}{}", self.base_path, route),
+            Mode::Hash => format!("#{}", route),
+        }
     }
 
-    fn get_route_from_location(location: &Location) -> String {
-        let path = location.pathname().unwrap();
-        let query = location.search().unwrap();
-        let fragment = location.hash().unwrap();
+    /// Splits the URL into path, query and fragment, each with its leading '/', '?' or '#'.
+    /// In `Mode::Path` the path is stripped of `base_path`, in `Mode::Hash` all three are taken from behind the '#'.
+    fn split_location(location: &Location, mode: Mode, base_path: &str) -> (String, String, String) {
+        match mode {
+            Mode::Path => {
+                let pathname = location.pathname().unwrap();
+                // Paths outside of the base are left alone, so they end up as not found.
+                let path = match pathname.get(base_path.len()..) {
+                    Some(rest) if pathname.starts_with(base_path) && (rest.is_empty() || rest.starts_with('/')) => {
+                        format!("/{}", rest.trim_start_matches('/'))
+                    }
+                    _ => pathname.clone(),
+                };
+                (path, location.search().unwrap(), location.hash().unwrap())
+            }
+            Mode::Hash => {
+                let hash = location.hash().unwrap();
+                let route = hash.trim_start_matches('#');
+                let (route, fragment) = match route.find('#') {
+                    Some(idx) => (&route[..idx], &route[idx..]),
+                    None => (route, ""),
+                };
+                let (path, query) = match route.find('?') {
+                    Some(idx) => (&route[..idx], &route[idx..]),
+                    None => (route, ""),
+                };
+                let path = if path.starts_with('/') {
+                    path.to_string()
+                } else {
+                    format!("/{}", path)
+                };
+                (path, query.to_string(), fragment.to_string())
+            }
+        }
+    }
+
+    fn get_route_from_location(location: &Location, mode: Mode, base_path: &str) -> String {
+        let (path, query, fragment) = Self::split_location(location, mode, base_path);
         format!("{path}{query}{fragment}",
             path=path,
             query=query,
             fragment=fragment)
     }
 
+    /// Gets the state stored with the current history entry,
+    /// `None` for entries the app did not create itself.
+    pub fn get_state(&self) -> Option<T> {
+        T::try_from(history_state()).ok()
+    }
+
     /// Gets the concatenated path, query, and fragment strings
     pub fn get_route(&self) -> String {
-        Self::get_route_from_location(&self.location)
+        Self::get_route_from_location(&self.location, self.mode, &self.base_path)
     }
 
     /// Gets the path name of the current url.
     pub fn get_path(&self) -> String {
-        self.location.pathname().unwrap()
+        Self::split_location(&self.location, self.mode, &self.base_path).0
     }
 
     /// Gets the query string of the current url.
     pub fn get_query(&self) -> String {
-        self.location.search().unwrap()
+        Self::split_location(&self.location, self.mode, &self.base_path).1
     }
 
     /// Gets the fragment of the current url.
     pub fn get_fragment(&self) -> String {
-        self.location.hash().unwrap()
+        Self::split_location(&self.location, self.mode, &self.base_path).2
     }
 }
\ No newline at end of file
@@ -0,0 +1,262 @@
+//! Service to persist the board, its workflow, users, teams and the signed in user in the browser's local storage.
+
+use serde::Serialize;
+use stdweb::web::{window, Storage};
+use yew::format::{Json, Text};
+use yew::services::storage::{Area, StorageService};
+
+use crate::models::status::Status;
+use crate::models::role::Role;
+use crate::models::task::{Task, TaskId};
+use crate::models::team::{Team, TeamId};
+use crate::models::user::{Avatar, User, UserId};
+use crate::models::workflow::Workflow;
+
+const KEY: &'static str = "yew-kanban.board";
+const WORKFLOW_KEY: &'static str = "yew-kanban.workflow";
+const USERS_KEY: &'static str = "yew-kanban.users";
+const TEAMS_KEY: &'static str = "yew-kanban.teams";
+const SESSION_KEY: &'static str = "yew-kanban.session";
+
+/// Version written by this build. Bump it whenever the layout of `Snapshot`
+/// changes in a way `#[serde(default)]` alone cannot absorb, and teach
+/// `Snapshot::migrate` how to bring older versions up to date.
+pub const VERSION: u32 = 3;
+
+/// Everything the board keeps between page reloads.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Missing in data written before versioning existed, which reads as `0`.
+    #[serde(default)]
+    pub version: u32,
+    pub tasks: Vec<Task>,
+    pub new_task: Task,
+    /// The id the next created task will get. Absent before version 2.
+    #[serde(default)]
+    pub next_id: TaskId,
+    /// The id the next created user will get, so ids of removed users are never handed out again.
+    /// Absent in older data, which reads as `0` and leaves the store to derive it from the users.
+    #[serde(default)]
+    pub next_user_id: UserId,
+    /// Like `next_user_id`, for teams.
+    #[serde(default)]
+    pub next_team_id: TeamId,
+}
+
+impl Snapshot {
+    pub fn new(
+        tasks: Vec<Task>,
+        new_task: Task,
+        next_id: TaskId,
+        next_user_id: UserId,
+        next_team_id: TeamId,
+    ) -> Self {
+        Snapshot {
+            version: VERSION,
+            tasks,
+            new_task,
+            next_id,
+            next_user_id,
+            next_team_id,
+        }
+    }
+
+    /// Upgrades a snapshot read from storage to the current `VERSION`.
+    /// Returns `None` for snapshots written by a newer build.
+    fn migrate(mut self) -> Option<Self> {
+        if self.version > VERSION {
+            return None;
+        }
+        if self.version < 2 {
+            // Tasks used to be addressed by their position, so hand out ids in that order.
+            for (idx, task) in self.tasks.iter_mut().enumerate() {
+                task.id = idx as TaskId + 1;
+            }
+            self.next_id = self.tasks.len() as TaskId + 1;
+        }
+        if self.version < 3 {
+            // Statuses used to be a fixed enum stored under its variant names.
+            for task in self.tasks.iter_mut() {
+                task.status = legacy_status(&task.status);
+            }
+        }
+        self.version = VERSION;
+        Some(self)
+    }
+}
+
+fn legacy_status(status: &Status) -> Status {
+    let key = match status.as_str() {
+        "InProgress" => "in_progress",
+        "Review" => "review",
+        "Testing" => "testing",
+        "Ready" => "ready",
+        "Done" => "done",
+        _ => "todo",
+    };
+    Status::from(key)
+}
+
+/// The users every fresh board starts with.
+fn default_users() -> Vec<User> {
+    vec![
+        User {
+            role: Role::Admin,
+            ..User::new(1, "John".to_string(), "Doe".to_string(), Avatar::Empty)
+        },
+        User::new(
+            2,
+            "Jane".to_string(),
+            "Smith".to_string(),
+            Avatar::Emoji("🐶".to_string()),
+        ),
+    ]
+}
+
+/// A thin wrapper around `StorageService` that knows where the board lives
+/// and how to read older formats of it.
+pub struct BoardStorage {
+    storage: StorageService,
+    /// Written to directly, `StorageService::store` panics when the storage is full.
+    local: Storage,
+}
+
+impl BoardStorage {
+    pub fn new() -> Self {
+        BoardStorage {
+            storage: StorageService::new(Area::Local),
+            local: window().local_storage(),
+        }
+    }
+
+    fn write<T: Serialize>(&mut self, key: &str, value: &T) -> Result<(), String> {
+        let text: Text = Json(value).into();
+        let text = text.map_err(|error| error.to_string())?;
+        self.local
+            .insert(key, &text)
+            .map_err(|_| "The browser's storage is full, recent changes will be lost on reload.".to_string())
+    }
+
+    /// Loads the saved board, if there is one that can still be read.
+    pub fn restore(&mut self) -> Option<Snapshot> {
+        match self.storage.restore(KEY) {
+            Json(Ok(snapshot)) => Snapshot::migrate(snapshot),
+            _ => None,
+        }
+    }
+
+    pub fn store(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        self.write(KEY, snapshot)
+    }
+
+    /// Loads the saved workflow, falling back to the default columns.
+    pub fn restore_workflow(&mut self) -> Workflow {
+        match self.storage.restore(WORKFLOW_KEY) {
+            Json(Ok(workflow)) => workflow,
+            _ => Workflow::default(),
+        }
+    }
+
+    pub fn store_workflow(&mut self, workflow: &Workflow) -> Result<(), String> {
+        self.write(WORKFLOW_KEY, workflow)
+    }
+
+    /// Loads the saved users, falling back to a couple of sample ones.
+    pub fn restore_users(&mut self) -> Vec<User> {
+        match self.storage.restore(USERS_KEY) {
+            Json(Ok(users)) => users,
+            _ => default_users(),
+        }
+    }
+
+    pub fn store_users(&mut self, users: &[User]) -> Result<(), String> {
+        self.write(USERS_KEY, &users)
+    }
+
+    /// Loads the saved teams, a fresh board has none.
+    pub fn restore_teams(&mut self) -> Vec<Team> {
+        match self.storage.restore(TEAMS_KEY) {
+            Json(Ok(teams)) => teams,
+            _ => vec![],
+        }
+    }
+
+    pub fn store_teams(&mut self, teams: &[Team]) -> Result<(), String> {
+        self.write(TEAMS_KEY, &teams)
+    }
+
+    /// Loads who used the app last on this browser.
+    pub fn restore_session(&mut self) -> Option<UserId> {
+        match self.storage.restore(SESSION_KEY) {
+            Json(Ok(user_id)) => user_id,
+            _ => None,
+        }
+    }
+
+    pub fn store_session(&mut self, user_id: Option<UserId>) -> Result<(), String> {
+        self.write(SESSION_KEY, &user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(version: u32, statuses: &[&str]) -> Snapshot {
+        let tasks = statuses
+            .iter()
+            .map(|status| Task {
+                status: Status::from(*status),
+                ..Task::create_empty()
+            })
+            .collect();
+        Snapshot {
+            version,
+            tasks,
+            new_task: Task::create_empty(),
+            next_id: 0,
+            next_user_id: 0,
+            next_team_id: 0,
+        }
+    }
+
+    #[test]
+    fn migrate_hands_out_ids_in_board_order() {
+        let migrated = snapshot(1, &["todo", "done"]).migrate().unwrap();
+        let ids: Vec<TaskId> = migrated.tasks.iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(migrated.next_id, 3);
+        assert_eq!(migrated.version, VERSION);
+    }
+
+    #[test]
+    fn migrate_keeps_ids_of_current_snapshots() {
+        let mut current = snapshot(VERSION, &["todo"]);
+        current.tasks[0].id = 9;
+        current.next_id = 10;
+        let migrated = current.migrate().unwrap();
+        assert_eq!(migrated.tasks[0].id, 9);
+        assert_eq!(migrated.next_id, 10);
+    }
+
+    #[test]
+    fn migrate_maps_legacy_statuses_to_column_keys() {
+        let migrated = snapshot(2, &["Todo", "InProgress", "Review", "Testing", "Ready", "Done"])
+            .migrate()
+            .unwrap();
+        let statuses: Vec<&str> = migrated.tasks.iter().map(|task| task.status.as_str()).collect();
+        assert_eq!(statuses, vec!["todo", "in_progress", "review", "testing", "ready", "done"]);
+    }
+
+    #[test]
+    fn migrate_keeps_current_statuses() {
+        let migrated = snapshot(VERSION, &["in_progress", "custom"]).migrate().unwrap();
+        let statuses: Vec<&str> = migrated.tasks.iter().map(|task| task.status.as_str()).collect();
+        assert_eq!(statuses, vec!["in_progress", "custom"]);
+    }
+
+    #[test]
+    fn migrate_refuses_newer_snapshots() {
+        assert!(snapshot(VERSION + 1, &[]).migrate().is_none());
+    }
+}
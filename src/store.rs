@@ -0,0 +1,432 @@
+//! Agent that owns the application state and shares it between components.
+
+use stdweb::web::Date;
+use yew::prelude::worker::*;
+
+use std::collections::HashSet;
+
+use crate::models::role::Role;
+use crate::models::status::Status;
+use crate::models::task::{Task, TaskId};
+use crate::models::team::{Team, TeamId};
+use crate::models::user::{User, UserId};
+use crate::models::workflow::Workflow;
+use crate::storage::{BoardStorage, Snapshot};
+
+/// Everything the views of the app render from.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    pub workflow: Workflow,
+    pub users: Vec<User>,
+    pub teams: Vec<Team>,
+    pub tasks: Vec<Task>,
+    /// The half-typed task of the board's header.
+    pub new_task: Task,
+    pub next_task_id: TaskId,
+    /// Never reused, unlike the ids of removed users.
+    pub next_user_id: UserId,
+    /// Never reused, unlike the ids of removed teams.
+    pub next_team_id: TeamId,
+    /// The user the app acts for, which decides what they may do.
+    pub current_user: Option<UserId>,
+    /// Why the last change could not be saved, if it could not.
+    pub storage_error: Option<String>,
+}
+
+impl State {
+    pub fn signed_in_user(&self) -> Option<&User> {
+        self.current_user
+            .and_then(|id| self.users.iter().find(|user| user.id == id))
+    }
+
+    /// Whether giving user `id` another role, or removing them with `None`, would leave
+    /// nobody who can manage users.
+    pub fn would_leave_no_admin(&self, id: UserId, role: Option<Role>) -> bool {
+        role != Some(Role::Admin)
+            && self.users.iter().any(|user| user.id == id && user.role == Role::Admin)
+            && !self.users.iter().any(|user| user.id != id && user.role == Role::Admin)
+    }
+
+    pub fn current_role(&self) -> Role {
+        match self.signed_in_user() {
+            Some(user) => user.role,
+            // Without any users nobody could add the first one.
+            None if self.users.is_empty() => Role::Admin,
+            None => Role::Viewer,
+        }
+    }
+
+    pub fn task(&self, id: TaskId) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    fn task_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    /// Tasks shown in a column, in board order.
+    pub fn column_tasks<'a>(&'a self, status: &'a Status) -> impl Iterator<Item = &'a Task> + 'a {
+        self.tasks
+            .iter()
+            .filter(move |task| !task.archived && &task.status == status)
+    }
+
+    pub fn team(&self, id: TeamId) -> Option<&Team> {
+        self.teams.iter().find(|team| team.id == id)
+    }
+
+    /// Whether a task was handed to the team or to one of its members.
+    pub fn is_team_task(&self, task: &Task, id: TeamId) -> bool {
+        task.team == Some(id)
+            || match (task.assignee, self.team(id)) {
+                (Some(assignee), Some(team)) => team.has_member(assignee),
+                _ => false,
+            }
+    }
+
+    /// Hours of estimated work assigned to a user that is not done yet.
+    /// Estimates can be anything typed into the form, so the sum stops at `u32::MAX`.
+    pub fn workload(&self, user_id: UserId) -> u32 {
+        self.tasks
+            .iter()
+            .filter(|task| {
+                !task.archived
+                    && task.assignee == Some(user_id)
+                    && !self.workflow.is_done(&task.status)
+            })
+            .map(|task| task.estimate)
+            .fold(0, u32::saturating_add)
+    }
+
+    fn add_task(&mut self, task: Task) {
+        let status = match self.workflow.first() {
+            Some(status) => status.clone(),
+            None => return,
+        };
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+        self.tasks.push(Task { id, status, ..task });
+    }
+
+    fn move_task(&mut self, id: TaskId, to: Status, position: usize) {
+        let idx = match self.tasks.iter().position(|task| task.id == id) {
+            Some(idx) => idx,
+            None => return,
+        };
+        // Within the same column the task's own slot disappears once it is removed.
+        let position = match self.tasks[..idx].iter().filter(|e| !e.archived && e.status == to).count() {
+            current if self.tasks[idx].status == to && current < position => position - 1,
+            _ => position,
+        };
+        let mut task = self.tasks.remove(idx);
+        if !self.workflow.is_done(&task.status) && self.workflow.is_done(&to) {
+            task.completed_at = Some(Date::now());
+        } else if !self.workflow.is_done(&to) {
+            task.completed_at = None;
+        }
+        task.status = to;
+
+        // `position` counts tasks of the target column only, translate it into an index in `tasks`.
+        let insert_at = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !e.archived && e.status == task.status)
+            .map(|(idx, _)| idx)
+            .nth(position)
+            .unwrap_or(self.tasks.len());
+        self.tasks.insert(insert_at, task);
+    }
+
+    fn add_user(&mut self, user: User) {
+        let id = self.next_user_id;
+        self.next_user_id += 1;
+        self.users.push(User { id, ..user });
+    }
+
+    /// Removes a user, handing their tasks to `reassign_to` or leaving them unassigned.
+    fn remove_user(&mut self, id: UserId, reassign_to: Option<UserId>) {
+        self.users.retain(|user| user.id != id);
+        if self.current_user == Some(id) {
+            self.current_user = None;
+        }
+        for task in self.tasks.iter_mut().filter(|task| task.assignee == Some(id)) {
+            task.assignee = reassign_to;
+        }
+        for team in self.teams.iter_mut().filter(|team| team.has_member(id)) {
+            team.toggle_member(id);
+        }
+    }
+
+    fn add_team(&mut self, team: Team) {
+        let id = self.next_team_id;
+        self.next_team_id += 1;
+        self.teams.push(Team { id, ..team });
+    }
+
+    /// Removes a team, its tasks keep their assignees but belong to no team anymore.
+    fn remove_team(&mut self, id: TeamId) {
+        self.teams.retain(|team| team.id != id);
+        for task in self.tasks.iter_mut().filter(|task| task.team == Some(id)) {
+            task.team = None;
+        }
+    }
+
+    /// Columns may have been removed from the workflow, collect their tasks in the first one.
+    fn adopt_orphaned_tasks(&mut self) {
+        let first = match self.workflow.first() {
+            Some(first) => first.clone(),
+            None => return,
+        };
+        let workflow = &self.workflow;
+        for task in self.tasks.iter_mut().filter(|task| !workflow.contains(&task.status)) {
+            task.status = first.clone();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Action {
+    UpdateNewTask(Task),
+    /// Adds a task to the first column, handing out its id.
+    AddTask(Task),
+    /// Saves the name, assignee, team and estimate of an existing task.
+    UpdateTask(Task),
+    /// Moves a task into `to`, placing it before the `position`-th task of that column.
+    MoveTask {
+        id: TaskId,
+        to: Status,
+        position: usize,
+        reason: Option<String>,
+    },
+    DeleteTask(TaskId),
+    /// Puts a deleted task back where it was.
+    RestoreTask(usize, Task),
+    SetArchived(TaskId, bool),
+    AssignTask(TaskId, Option<UserId>),
+    SetWorkflow(Workflow),
+    /// Adds a user, handing out its id.
+    AddUser(User),
+    UpdateUser(User),
+    RemoveUser {
+        id: UserId,
+        reassign_to: Option<UserId>,
+    },
+    /// Makes the app act for another user.
+    SwitchUser(UserId),
+    /// Adds a team, handing out its id.
+    AddTeam(Team),
+    UpdateTeam(Team),
+    RemoveTeam(TeamId),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Request {
+    /// Applies an action and broadcasts the new state to all connected components.
+    Dispatch(Action),
+    GetState,
+}
+
+impl Transferable for Request {}
+impl Transferable for State {}
+
+/// The Store worker holds the application state, persists it and keeps every view in sync.
+pub struct Store {
+    link: AgentLink<Store>,
+    storage: BoardStorage,
+    state: State,
+    /// A list of all entities connected to the store.
+    /// Every change of the state is broadcast to all of them.
+    subscribers: HashSet<HandlerId>,
+}
+
+impl Store {
+    fn save(&mut self) {
+        let snapshot = Snapshot::new(
+            self.state.tasks.clone(),
+            self.state.new_task.clone(),
+            self.state.next_task_id,
+            self.state.next_user_id,
+            self.state.next_team_id,
+        );
+        let storage = &mut self.storage;
+        let state = &self.state;
+        let saved = storage
+            .store(&snapshot)
+            .and_then(|_| storage.store_workflow(&state.workflow))
+            .and_then(|_| storage.store_users(&state.users))
+            .and_then(|_| storage.store_teams(&state.teams))
+            .and_then(|_| storage.store_session(state.current_user));
+        self.state.storage_error = saved.err();
+    }
+
+    fn apply(&mut self, action: Action) {
+        let state = &mut self.state;
+        match action {
+            Action::UpdateNewTask(task) => {
+                state.new_task = task;
+            }
+
+            Action::AddTask(task) => state.add_task(task),
+
+            Action::UpdateTask(edited) => {
+                if let Some(task) = state.task_mut(edited.id) {
+                    task.name = edited.name;
+                    task.assignee = edited.assignee;
+                    task.team = edited.team;
+                    task.estimate = edited.estimate;
+                }
+            }
+
+            Action::MoveTask {
+                id,
+                to,
+                position,
+                reason,
+            } => {
+                if reason.is_some() {
+                    if let Some(task) = state.task_mut(id) {
+                        task.reason = reason;
+                    }
+                }
+                state.move_task(id, to, position);
+            }
+
+            Action::DeleteTask(id) => state.tasks.retain(|task| task.id != id),
+
+            Action::RestoreTask(idx, task) => {
+                let idx = idx.min(state.tasks.len());
+                state.tasks.insert(idx, task);
+            }
+
+            Action::SetArchived(id, archived) => {
+                if let Some(task) = state.task_mut(id) {
+                    task.archived = archived;
+                }
+            }
+
+            Action::AssignTask(id, assignee) => {
+                if let Some(task) = state.task_mut(id) {
+                    task.assignee = assignee;
+                }
+            }
+
+            Action::SetWorkflow(workflow) => {
+                state.workflow = workflow;
+                state.adopt_orphaned_tasks();
+            }
+
+            Action::AddUser(user) => state.add_user(user),
+
+            Action::UpdateUser(edited) => {
+                if state.would_leave_no_admin(edited.id, Some(edited.role)) {
+                    return;
+                }
+                if let Some(user) = state.users.iter_mut().find(|user| user.id == edited.id) {
+                    *user = edited;
+                }
+            }
+
+            Action::RemoveUser { id, reassign_to } => {
+                if !state.would_leave_no_admin(id, None) {
+                    state.remove_user(id, reassign_to);
+                }
+            }
+
+            Action::SwitchUser(id) => {
+                if state.users.iter().any(|user| user.id == id) {
+                    state.current_user = Some(id);
+                }
+            }
+
+            Action::AddTeam(team) => state.add_team(team),
+
+            Action::UpdateTeam(edited) => {
+                if let Some(team) = state.teams.iter_mut().find(|team| team.id == edited.id) {
+                    *team = edited;
+                }
+            }
+
+            Action::RemoveTeam(id) => state.remove_team(id),
+        }
+    }
+}
+
+impl Agent for Store {
+    type Reach = Context;
+    type Message = ();
+    type Input = Request;
+    type Output = State;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        let mut storage = BoardStorage::new();
+        let (tasks, new_task, next_task_id, next_user_id, next_team_id) = match storage.restore() {
+            Some(snapshot) => (
+                snapshot.tasks,
+                snapshot.new_task,
+                snapshot.next_id,
+                snapshot.next_user_id,
+                snapshot.next_team_id,
+            ),
+            None => (vec![], Task::create_empty(), 1, 0, 0),
+        };
+        let users = storage.restore_users();
+        let teams = storage.restore_teams();
+        // Boards saved before the counters existed only know the ids in use.
+        let next_user_id = users.iter().map(|user| user.id + 1).fold(next_user_id.max(1), UserId::max);
+        let next_team_id = teams.iter().map(|team| team.id + 1).fold(next_team_id.max(1), TeamId::max);
+        // Until someone picks a user, act for the first admin.
+        let current_user = match storage.restore_session() {
+            Some(id) if users.iter().any(|user| user.id == id) => Some(id),
+            _ => users
+                .iter()
+                .find(|user| user.role == Role::Admin)
+                .or_else(|| users.first())
+                .map(|user| user.id),
+        };
+        let mut state = State {
+            workflow: storage.restore_workflow(),
+            users,
+            teams,
+            tasks,
+            new_task,
+            next_task_id,
+            next_user_id,
+            next_team_id,
+            current_user,
+            storage_error: None,
+        };
+        state.adopt_orphaned_tasks();
+
+        Store {
+            link,
+            storage,
+            state,
+            subscribers: HashSet::new(),
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) {}
+
+    fn handle(&mut self, msg: Self::Input, who: HandlerId) {
+        match msg {
+            Request::Dispatch(action) => {
+                self.apply(action);
+                self.save();
+                for sub in self.subscribers.iter() {
+                    self.link.response(*sub, self.state.clone());
+                }
+            }
+            Request::GetState => {
+                self.link.response(who, self.state.clone());
+            }
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        self.subscribers.insert(id);
+    }
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+}
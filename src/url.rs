@@ -0,0 +1,191 @@
+//! Percent-encoding of the parts of a URL and a parsed query string for `router::Route`.
+
+use std::fmt;
+
+/// Characters that never need escaping, see RFC 3986.
+fn is_unreserved(byte: u8) -> bool {
+    match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => true,
+        _ => false,
+    }
+}
+
+/// Escapes everything but unreserved characters, so the result can be used as
+/// a path segment, query key or value, or fragment.
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if is_unreserved(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Reverses `percent_encode`. Malformed escapes are kept as they are.
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = match bytes.get(idx..idx + 3) {
+            Some(&[b'%', high, low]) => match (hex_value(high), hex_value(low)) {
+                (Some(high), Some(low)) => Some(high * 16 + low),
+                _ => None,
+            },
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The parameters of a query string, in the order they appear in it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Query {
+    params: Vec<(String, String)>,
+}
+
+impl Query {
+    /// Parses a query string without its leading '?'. A `+` reads as a space, like in forms.
+    pub fn parse(query: &str) -> Self {
+        let params = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let mut parts = param.splitn(2, '=');
+                let key = parts.next().unwrap_or("").replace('+', " ");
+                let value = parts.next().unwrap_or("").replace('+', " ");
+                (percent_decode(&key), percent_decode(&value))
+            })
+            .collect();
+        Query { params }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// The first value given for `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replaces all values of `key` with `value`, keeping its place if it was there before.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.params.iter().position(|(param, _)| param == key) {
+            Some(idx) => {
+                self.params[idx].1 = value.to_string();
+                let mut seen = 0;
+                self.params.retain(|(param, _)| {
+                    if param != key {
+                        return true;
+                    }
+                    seen += 1;
+                    seen == 1
+                });
+            }
+            None => self.params.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.params.retain(|(param, _)| param != key);
+    }
+}
+
+impl fmt::Display for Query {
+    /// The encoded query string, without a leading '?'.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+            .collect();
+        write!(f, "{}", params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_round_trips() {
+        for value in &["with space", "a/b", "🐶 emoji", "100%", "plain-_.~"] {
+            assert_eq!(percent_decode(&percent_encode(value)), *value);
+        }
+    }
+
+    #[test]
+    fn encode_escapes_reserved_characters() {
+        assert_eq!(percent_encode("a b/c"), "a%20b%2Fc");
+        assert_eq!(percent_encode("🐶"), "%F0%9F%90%B6");
+        assert_eq!(percent_encode("plain-_.~"), "plain-_.~");
+    }
+
+    #[test]
+    fn decode_keeps_malformed_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%41%"), "A%");
+    }
+
+    #[test]
+    fn query_reads_plus_as_space() {
+        let query = Query::parse("q=two+words&tag=a%2Bb");
+        assert_eq!(query.get("q"), Some("two words"));
+        assert_eq!(query.get("tag"), Some("a+b"));
+    }
+
+    #[test]
+    fn query_round_trips() {
+        let mut query = Query::default();
+        query.set("search", "a b/c 🐶");
+        query.set("plus", "1+1");
+        let parsed = Query::parse(&query.to_string());
+        assert_eq!(parsed, query);
+        assert_eq!(parsed.get("search"), Some("a b/c 🐶"));
+        assert_eq!(parsed.get("plus"), Some("1+1"));
+    }
+
+    #[test]
+    fn query_set_replaces_every_value_in_place() {
+        let mut query = Query::parse("a=1&b=2&a=3");
+        query.set("a", "4");
+        assert_eq!(query.to_string(), "a=4&b=2");
+        query.remove("a");
+        assert_eq!(query.to_string(), "b=2");
+    }
+
+    #[test]
+    fn query_skips_empty_params() {
+        let query = Query::parse("&a=1&&b");
+        assert_eq!(query.get("a"), Some("1"));
+        assert_eq!(query.get("b"), Some(""));
+        assert!(Query::parse("").is_empty());
+    }
+}